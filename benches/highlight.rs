@@ -0,0 +1,93 @@
+//! Measures the time and peak heap usage of highlighting and tokenizing a multi-megabyte input.
+//! Run with `cargo bench`.
+
+use dialect::Highlight;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+const SOURCES: &[&str] = &[
+    include_str!("../src/lexer.rs"),
+    include_str!("../src/parser.rs"),
+    include_str!("../src/grammar/expr.rs"),
+    include_str!("../src/grammar/item.rs"),
+    include_str!("../src/diagnostic.rs"),
+];
+
+fn input(len: usize) -> String {
+    let mut input = String::with_capacity(len + SOURCES[0].len());
+
+    for source in SOURCES.iter().cycle() {
+        if input.len() >= len {
+            break;
+        }
+        input.push_str(source);
+    }
+
+    input
+}
+
+// Reports the fastest of several runs of `f`, and how much more memory than `input` was in use at
+// its peak.
+fn bench<T>(name: &str, input: &str, f: impl Fn(&str) -> T) {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+
+    let elapsed = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            let output = f(input);
+            let elapsed = start.elapsed();
+            drop(output);
+
+            elapsed
+        })
+        .min()
+        .unwrap();
+
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+
+    println!(
+        "{:<10} {:>5} MiB input: {:>8.1?}, peak heap {:>6.1} MiB ({:.1}x input)",
+        name,
+        input.len() >> 20,
+        elapsed,
+        peak as f64 / (1 << 20) as f64,
+        peak as f64 / input.len() as f64,
+    );
+}
+
+fn main() {
+    for mib in [1, 4, 16] {
+        let input = input(mib << 20);
+
+        bench("highlight", &input, |input| {
+            syntax_rust::Highlighter.highlight(input)
+        });
+        bench("tokenize", &input, syntax_rust::token::tokenize);
+    }
+}
@@ -0,0 +1,231 @@
+//! Problems in Rust source code that are found while lexing it.
+
+use crate::lexer::{lex_with_errors, scan_escape, Mode};
+use crate::{Token, TokenKind};
+
+/// A problem found in the input, such as an unterminated string or an invalid escape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Diagnostic {
+    /// the byte range the problem covers
+    pub range: std::ops::Range<usize>,
+    /// a description of the problem, suitable for showing to the user
+    pub message: String,
+}
+
+/// Finds the problems in `input` that can be detected by the lexer, in the order they appear.
+/// Each of them covers a range that is highlighted as an error.
+pub fn diagnostics(input: &str) -> Vec<Diagnostic> {
+    let (tokens, errors) = lex_with_errors(input);
+
+    let mut diagnostics: Vec<_> = errors
+        .into_iter()
+        .map(|(range, error)| Diagnostic {
+            range,
+            message: error.to_string(),
+        })
+        .collect();
+
+    for token in &tokens {
+        push_literal_diagnostics(input, token, &mut diagnostics);
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+
+    diagnostics
+}
+
+// Literals are lexed as single tokens even when what’s inside them is invalid, so that the rest of
+// the literal can still be highlighted; the problems inside them are found here.
+fn push_literal_diagnostics(input: &str, token: &Token, diagnostics: &mut Vec<Diagnostic>) {
+    let (mode, is_raw) = match token.kind {
+        TokenKind::String => (Mode::Str, false),
+        TokenKind::ByteString => (Mode::ByteStr, false),
+        TokenKind::CString => (Mode::CStr, false),
+        TokenKind::Char => (Mode::Char, false),
+        TokenKind::Byte => (Mode::Byte, false),
+        TokenKind::RawString => (Mode::Str, true),
+        TokenKind::RawByteString => (Mode::ByteStr, true),
+        TokenKind::RawCString => (Mode::CStr, true),
+        _ => return,
+    };
+
+    let text = &input[token.range.clone()];
+    let quote_idx = text.find(&['"', '\''][..]).unwrap();
+    let closing_len = if is_raw {
+        1 + text[..quote_idx].bytes().filter(|b| *b == b'#').count()
+    } else {
+        1
+    };
+
+    let contents_start = token.range.start + quote_idx + 1;
+    let contents = &text[quote_idx + 1..text.len() - closing_len];
+    let mut idx = 0;
+
+    while let Some(c) = contents[idx..].chars().next() {
+        let start = contents_start + idx;
+
+        if c == '\\' && !is_raw {
+            let escape = scan_escape(&contents[idx..], mode);
+
+            if !escape.is_valid {
+                diagnostics.push(Diagnostic {
+                    range: start..start + escape.len,
+                    message: format!("invalid escape `{}`", &contents[idx..idx + escape.len]),
+                });
+            }
+
+            idx += escape.len;
+            continue;
+        }
+
+        if !mode.allows_char(c) {
+            diagnostics.push(Diagnostic {
+                range: start..start + c.len_utf8(),
+                message: disallowed_char_message(mode).to_string(),
+            });
+        }
+
+        idx += c.len_utf8();
+    }
+}
+
+fn disallowed_char_message(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Byte => "non-ASCII character in byte literal",
+        Mode::ByteStr => "non-ASCII character in byte string literal",
+        Mode::CStr => "null character in C string literal",
+        Mode::Char | Mode::Str => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn check(input: &str, expected: &[(std::ops::Range<usize>, &str)]) {
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(range, message)| Diagnostic {
+                range: range.clone(),
+                message: message.to_string(),
+            })
+            .collect();
+
+        assert_eq!(diagnostics(input), expected);
+    }
+
+    #[test]
+    fn valid_input_has_no_diagnostics() {
+        check(r#"fn a() { let b = "\n" + 'c' + 0b1u8 + b"d"; }"#, &[]);
+    }
+
+    #[test]
+    fn reports_unterminated_string() {
+        check("a \"b", &[(2..4, "unterminated string")]);
+    }
+
+    #[test]
+    fn reports_unterminated_raw_string() {
+        check("r#\"a\"", &[(0..5, "unterminated raw string")]);
+    }
+
+    #[test]
+    fn reports_unterminated_block_comment() {
+        check("/* a /* b */", &[(0..12, "unterminated block comment")]);
+    }
+
+    #[test]
+    fn reports_invalid_escape() {
+        check(r#""a\qb""#, &[(2..4, r"invalid escape `\q`")]);
+    }
+
+    #[test]
+    fn reports_invalid_digit_for_base() {
+        check("0b102", &[(0..5, "invalid digit for base 2")]);
+    }
+
+    #[test]
+    fn reports_invalid_suffix() {
+        check(
+            "1u7 2.0f16",
+            &[
+                (0..3, "invalid suffix `u7` for number literal"),
+                (4..10, "invalid suffix `f16` for number literal"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_missing_digits() {
+        check(
+            "0x 1e",
+            &[
+                (0..2, "no valid digits found for number"),
+                (3..5, "expected at least one digit in exponent"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_unknown_start_of_token() {
+        check(
+            "a € `",
+            &[
+                (2..5, "unknown start of token: €"),
+                (6..7, "unknown start of token: `"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_malformed_char_literals() {
+        check(
+            "'' '12' '\t' 'ab' '1",
+            &[
+                (0..2, "empty char literal"),
+                (3..7, "char literal may only contain one character"),
+                (8..11, r"`\t` must be escaped in a char literal"),
+                (12..16, "char literal may only contain one character"),
+                (17..19, "unterminated char literal"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_chars_not_allowed_in_literal() {
+        check(
+            r#"b"é" c"\0""#,
+            &[
+                (2..4, "non-ASCII character in byte string literal"),
+                (8..10, r"invalid escape `\0`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn reports_reserved_raw_identifier() {
+        check("r#self", &[(0..6, "`self` cannot be a raw identifier")]);
+        check("r#_", &[(0..3, "`_` cannot be a raw identifier")]);
+    }
+
+    #[test]
+    fn reports_conflict_markers() {
+        check(
+            "<<<<<<< a\n=======\n>>>>>>> b",
+            &[
+                (0..9, "encountered git conflict marker `<<<<<<<`"),
+                (10..17, "encountered git conflict marker `=======`"),
+                (18..27, "encountered git conflict marker `>>>>>>>`"),
+            ],
+        );
+    }
+
+    #[test]
+    fn marker_lines_in_strings_and_comments_are_not_reported() {
+        check(
+            "const S: &str = r#\"\nTitle\n=======\n\"#;\n/*\n=======\n*/\nfn m() {}",
+            &[],
+        );
+    }
+}
@@ -1,9 +1,33 @@
+mod attr;
 mod block;
 mod expr;
 mod item;
 mod stmt;
+mod ty;
 
+pub(crate) use attr::parse_attr;
 pub(crate) use block::parse_block;
-pub(crate) use expr::parse_expr;
-pub(crate) use item::parse_item;
+pub(crate) use expr::{parse_expr, parse_literal, BLOCK_LIKE_START};
+pub(crate) use item::{at_item_start, parse_item, parse_macro_call, ITEM_START};
 pub(crate) use stmt::parse_stmt;
+pub(crate) use ty::{parse_generic_params, parse_ty};
+
+use crate::Parser;
+use dialect::HighlightGroup;
+
+/// Identifiers are classified by case, but names in FFI code and generated bindings often ignore
+/// the conventions (as in `struct sockaddr_in`), so wherever a name is being defined we accept an
+/// identifier of any case.
+pub(crate) const NAME: &[crate::TokenKind] = &[
+    crate::TokenKind::Ident,
+    crate::TokenKind::TypeIdent,
+    crate::TokenKind::ConstIdent,
+];
+
+pub(crate) fn parse_name(p: &mut Parser<'_>, group: HighlightGroup) {
+    if p.at(NAME) {
+        p.eat(group);
+    } else {
+        p.push(crate::TokenKind::Ident, group);
+    }
+}
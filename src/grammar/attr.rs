@@ -0,0 +1,85 @@
+use crate::Parser;
+use dialect::HighlightGroup;
+
+// Parses an outer (`#[…]`) or inner (`#![…]`) attribute. The whole attribute is highlighted as
+// such, so all we need to do is find the bracket that closes it.
+pub(crate) fn parse_attr(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Pound]));
+    p.eat(HighlightGroup::Attribute);
+
+    if p.at(&[crate::TokenKind::Bang]) {
+        p.eat(HighlightGroup::Attribute);
+    }
+
+    if !p.at(&[crate::TokenKind::OpenBracket]) {
+        p.push(crate::TokenKind::OpenBracket, HighlightGroup::Attribute);
+        return;
+    }
+
+    let mut depth = 0;
+
+    while let Some(token) = p.peek() {
+        match token.kind {
+            crate::TokenKind::OpenBracket => depth += 1,
+            crate::TokenKind::CloseBracket => depth -= 1,
+            _ => {}
+        }
+
+        p.eat(HighlightGroup::Attribute);
+
+        if depth == 0 {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_inner_attribute() {
+        let mut parser = Parser::new("#![a[b]] c");
+        parse_attr(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Attribute,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Attribute,
+                },
+            ],
+        );
+    }
+}
@@ -1,8 +1,8 @@
-use crate::grammar::parse_stmt;
+use crate::grammar::{at_item_start, parse_attr, parse_item, parse_stmt};
 use crate::Parser;
 use dialect::HighlightGroup;
 
-pub(crate) fn parse_block(p: &mut Parser) {
+pub(crate) fn parse_block(p: &mut Parser<'_>) {
     assert!(p.at(&[crate::TokenKind::OpenBrace]));
     p.eat(HighlightGroup::Delimiter);
 
@@ -16,6 +16,17 @@ pub(crate) fn parse_block(p: &mut Parser) {
             break;
         }
 
+        if p.at(&[crate::TokenKind::Pound]) {
+            parse_attr(p);
+            continue;
+        }
+
+        // Items can be nested inside blocks.
+        if at_item_start(p) {
+            parse_item(p);
+            continue;
+        }
+
         parse_stmt(p);
     }
 }
@@ -197,4 +208,252 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_nested_item() {
+        let mut parser = Parser::new("{ fn inner() {} let x = 1; }");
+        parse_block(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 2..4,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 5..10,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 16..19,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_nested_macro_rules() {
+        let mut parser = Parser::new("{ macro_rules! m { ($x:expr) => { $x } } let y = 1; }");
+        parse_block(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 2..13,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::MacroDef,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 20..22,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 23..27,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 29..31,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 34..36,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 37..38,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 39..40,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 41..44,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 45..46,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 47..48,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 49..50,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 50..51,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 52..53,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_macro_calls() {
+        let mut parser = Parser::new(r#"{ println!("{}", x); assert_eq!(a, b) }"#);
+        parse_block(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 2..9,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 12..14,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 21..30,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 33..34,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 35..36,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 36..37,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 38..39,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
 }
@@ -1,9 +1,148 @@
+mod ctrl_flow;
+mod literal;
+
+use crate::grammar::{parse_attr, parse_block, parse_macro_call, parse_ty, ITEM_START};
 use crate::Parser;
+use ctrl_flow::{
+    parse_for, parse_if, parse_jump, parse_labelled, parse_let, parse_loop, parse_match,
+    parse_while,
+};
 use dialect::{HighlightGroup, HighlightedSpan};
+pub(crate) use literal::parse_literal;
+
+/// The tokens that begin expressions which end in a block, and as such don’t need to be followed
+/// by a semicolon when used as statements.
+pub(crate) const BLOCK_LIKE_START: &[crate::TokenKind] = &[
+    crate::TokenKind::If,
+    crate::TokenKind::While,
+    crate::TokenKind::Loop,
+    crate::TokenKind::For,
+    crate::TokenKind::Match,
+    crate::TokenKind::Unsafe,
+    crate::TokenKind::OpenBrace,
+];
+
+const BINARY_OPER: &[crate::TokenKind] = &[
+    crate::TokenKind::Plus,
+    crate::TokenKind::Minus,
+    crate::TokenKind::Star,
+    crate::TokenKind::Slash,
+    crate::TokenKind::Percent,
+    crate::TokenKind::Caret,
+    crate::TokenKind::And,
+    crate::TokenKind::Pipe,
+    crate::TokenKind::AndAnd,
+    crate::TokenKind::PipePipe,
+    crate::TokenKind::Shl,
+    crate::TokenKind::Shr,
+    crate::TokenKind::DoubleEquals,
+    crate::TokenKind::BangEquals,
+    crate::TokenKind::Less,
+    crate::TokenKind::Greater,
+    crate::TokenKind::LessEquals,
+    crate::TokenKind::GreaterEquals,
+];
+
+const ASSIGN_OPER: &[crate::TokenKind] = &[
+    crate::TokenKind::Equals,
+    crate::TokenKind::PlusEquals,
+    crate::TokenKind::MinusEquals,
+    crate::TokenKind::StarEquals,
+    crate::TokenKind::SlashEquals,
+    crate::TokenKind::PercentEquals,
+    crate::TokenKind::CaretEquals,
+    crate::TokenKind::AndEquals,
+    crate::TokenKind::PipeEquals,
+    crate::TokenKind::ShlEquals,
+    crate::TokenKind::ShrEquals,
+];
+
+const RANGE_OPER: &[crate::TokenKind] = &[
+    crate::TokenKind::DotDot,
+    crate::TokenKind::DotDotEquals,
+    crate::TokenKind::DotDotDot,
+];
+
+pub(crate) fn parse_expr(p: &mut Parser<'_>, is_pattern: bool) {
+    if is_pattern {
+        parse_pattern(p);
+
+        while p.at(&[crate::TokenKind::Pipe]) {
+            p.eat(HighlightGroup::BinaryOper);
+            parse_pattern(p);
+        }
+
+        return;
+    }
+
+    parse_operand(p, false);
+    parse_postfix(p);
 
-pub(crate) fn parse_expr(p: &mut Parser, is_pattern: bool) {
+    if p.at(RANGE_OPER) {
+        p.eat(HighlightGroup::BinaryOper);
+
+        // The end of a range is optional, as in `1..` -- this also covers `for i in 0.. {}`.
+        if !at_expr_end(p) && !p.at(&[crate::TokenKind::OpenBrace]) {
+            parse_expr(p, false);
+        }
+    } else if p.at(BINARY_OPER) {
+        // Precedence doesn’t matter when highlighting, so we can simply parse everything to the
+        // right of the operator as its right-hand side.
+        p.eat(HighlightGroup::BinaryOper);
+        parse_expr(p, false);
+    } else if p.at(ASSIGN_OPER) {
+        p.eat(HighlightGroup::AssignOper);
+        parse_expr(p, false);
+    }
+}
+
+// Whether we’re at a token that cannot continue the current expression, which lets us tell if
+// optional parts of expressions are present.
+fn at_expr_end(p: &Parser<'_>) -> bool {
+    p.at_end()
+        || p.at(&[
+            crate::TokenKind::Semi,
+            crate::TokenKind::Comma,
+            crate::TokenKind::CloseParen,
+            crate::TokenKind::CloseBrace,
+            crate::TokenKind::CloseBracket,
+        ])
+}
+
+// Parses a pattern without alternatives (`a | b`), since closure parameters are delimited by `|`.
+fn parse_pattern(p: &mut Parser<'_>) {
+    parse_operand(p, true);
+
+    if p.at(&[crate::TokenKind::At]) {
+        p.eat(HighlightGroup::OtherOper);
+        parse_operand(p, true);
+    }
+
+    if p.at(RANGE_OPER) {
+        p.eat(HighlightGroup::BinaryOper);
+
+        // Range patterns can be open-ended, as in `1..`.
+        if p.at(&[
+            crate::TokenKind::Minus,
+            crate::TokenKind::Int,
+            crate::TokenKind::Float,
+            crate::TokenKind::Char,
+            crate::TokenKind::Byte,
+            crate::TokenKind::Ident,
+            crate::TokenKind::ConstIdent,
+        ]) {
+            parse_operand(p, true);
+        }
+    }
+}
+
+fn parse_operand(p: &mut Parser<'_>, is_pattern: bool) {
     if let Some(token) = p.peek() {
         match token.kind {
+            crate::TokenKind::ConstIdent => p.eat(HighlightGroup::ConstantUse),
+            crate::TokenKind::Ident if p.nth_at(1, &[crate::TokenKind::Bang]) => {
+                parse_macro_call(p)
+            }
             crate::TokenKind::Ident => {
                 let var = p.next().unwrap();
 
@@ -13,14 +152,7 @@ pub(crate) fn parse_expr(p: &mut Parser, is_pattern: bool) {
                         group: HighlightGroup::FunctionCall,
                     });
 
-                    let open_paren = p.next().unwrap();
-
-                    p.output.push(HighlightedSpan {
-                        range: open_paren.range,
-                        group: HighlightGroup::Delimiter,
-                    });
-
-                    p.push(crate::TokenKind::CloseParen, HighlightGroup::Delimiter);
+                    parse_tuple(p, false);
                 } else {
                     p.output.push(HighlightedSpan {
                         range: var.range,
@@ -32,15 +164,212 @@ pub(crate) fn parse_expr(p: &mut Parser, is_pattern: bool) {
                     });
                 }
             }
+            // Tuple structs and tuple-like enum variants, both when constructing and matching.
+            crate::TokenKind::TypeIdent => {
+                p.eat(HighlightGroup::TyUse);
+
+                if p.at(&[crate::TokenKind::OpenParen]) {
+                    parse_tuple(p, is_pattern);
+                }
+            }
 
             crate::TokenKind::OpenParen => parse_tuple(p, is_pattern),
+            crate::TokenKind::OpenBracket => parse_array(p, is_pattern),
+
+            crate::TokenKind::Mut | crate::TokenKind::Ref if is_pattern => {
+                p.eat(HighlightGroup::OtherKeyword);
+                parse_operand(p, is_pattern);
+            }
+            crate::TokenKind::Underscore => p.eat(HighlightGroup::OtherKeyword),
+
+            crate::TokenKind::And | crate::TokenKind::AndAnd => parse_ref(p, is_pattern),
+            crate::TokenKind::Star if !is_pattern => {
+                p.eat(HighlightGroup::PointerOper);
+                parse_operand(p, is_pattern);
+            }
+            crate::TokenKind::Minus | crate::TokenKind::Bang => {
+                p.eat(HighlightGroup::OtherOper);
+                parse_operand(p, is_pattern);
+            }
+            // A range with no start, as in `..5`.
+            crate::TokenKind::DotDot | crate::TokenKind::DotDotEquals if !is_pattern => {
+                p.eat(HighlightGroup::BinaryOper);
+
+                if !at_expr_end(p) && !p.at(&[crate::TokenKind::OpenBrace]) {
+                    parse_expr(p, false);
+                }
+            }
+
+            crate::TokenKind::Pipe | crate::TokenKind::PipePipe if !is_pattern => parse_closure(p),
+            crate::TokenKind::Move if !is_pattern => {
+                p.eat(HighlightGroup::OtherKeyword);
+                parse_operand(p, is_pattern);
+            }
+
+            crate::TokenKind::True | crate::TokenKind::False => p.eat(HighlightGroup::Boolean),
+            crate::TokenKind::SelfValue => p.eat(HighlightGroup::OtherKeyword),
+
+            crate::TokenKind::If => parse_if(p),
+            crate::TokenKind::Let if !is_pattern => parse_let(p),
+            crate::TokenKind::While => parse_while(p),
+            crate::TokenKind::Loop => parse_loop(p),
+            crate::TokenKind::For => parse_for(p),
+            crate::TokenKind::Match => parse_match(p),
+            crate::TokenKind::TickIdent
+                if !is_pattern && p.nth_at(1, &[crate::TokenKind::Colon]) =>
+            {
+                parse_labelled(p)
+            }
+            // A lifetime where it doesn’t belong, as in `&'a x`.
+            crate::TokenKind::TickIdent => p.eat(HighlightGroup::SpecialIdentUse),
+            crate::TokenKind::Return | crate::TokenKind::Break | crate::TokenKind::Continue => {
+                parse_jump(p)
+            }
+
+            crate::TokenKind::Unsafe => {
+                p.eat(HighlightGroup::OtherKeyword);
+
+                if p.at(&[crate::TokenKind::OpenBrace]) {
+                    parse_block(p);
+                }
+            }
+            crate::TokenKind::OpenBrace => parse_block(p),
+
+            crate::TokenKind::Int
+            | crate::TokenKind::Float
+            | crate::TokenKind::String
+            | crate::TokenKind::RawString
+            | crate::TokenKind::Char
+            | crate::TokenKind::Byte
+            | crate::TokenKind::ByteString
+            | crate::TokenKind::RawByteString
+            | crate::TokenKind::CString
+            | crate::TokenKind::RawCString => parse_literal(p),
 
+            // An item can’t be part of an expression, but it can follow one in a block that is
+            // missing its closing brace; we leave it alone so that the block can parse it.
+            _ if p.at(ITEM_START) => {}
             _ => p.eat(HighlightGroup::Error),
         }
     }
 }
 
-fn parse_tuple(p: &mut Parser, is_pattern: bool) {
+fn parse_postfix(p: &mut Parser<'_>) {
+    loop {
+        if p.at(&[crate::TokenKind::Dot]) {
+            parse_member_access(p);
+        } else if p.at(&[crate::TokenKind::Question]) {
+            p.eat(HighlightGroup::OtherOper);
+        } else if p.at(&[crate::TokenKind::OpenBracket]) {
+            p.eat(HighlightGroup::Delimiter);
+            parse_expr(p, false);
+            p.push(crate::TokenKind::CloseBracket, HighlightGroup::Delimiter);
+        } else if p.at(&[crate::TokenKind::As]) {
+            p.eat(HighlightGroup::OtherKeyword);
+            parse_ty(p);
+        } else {
+            break;
+        }
+    }
+}
+
+// Parses `&x`, `&mut x` and the raw borrows `&raw const x` and `&raw mut x`. `&&x` is a reference
+// to a reference, so it’s parsed the same way.
+fn parse_ref(p: &mut Parser<'_>, is_pattern: bool) {
+    assert!(p.at(&[crate::TokenKind::And, crate::TokenKind::AndAnd]));
+    p.eat(HighlightGroup::PointerOper);
+
+    // `raw` is only a keyword when followed by `const` or `mut`, so `&raw` borrows a variable.
+    if !is_pattern
+        && p.nth_at_contextual_kw(0, "raw")
+        && p.nth_at(1, &[crate::TokenKind::Const, crate::TokenKind::Mut])
+    {
+        p.eat(HighlightGroup::OtherKeyword);
+        p.eat(HighlightGroup::OtherKeyword);
+    } else if p.at(&[crate::TokenKind::Mut]) {
+        p.eat(HighlightGroup::OtherKeyword);
+    }
+
+    parse_operand(p, is_pattern);
+}
+
+fn parse_closure(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Pipe, crate::TokenKind::PipePipe]));
+
+    if p.at(&[crate::TokenKind::PipePipe]) {
+        p.eat(HighlightGroup::Delimiter);
+    } else {
+        p.eat(HighlightGroup::Delimiter);
+
+        loop {
+            if p.at_end() {
+                break;
+            }
+
+            if p.at(&[crate::TokenKind::Comma]) {
+                p.eat(HighlightGroup::Separator);
+            }
+
+            if p.at(&[crate::TokenKind::Pipe]) {
+                p.eat(HighlightGroup::Delimiter);
+                break;
+            }
+
+            if p.at(&[crate::TokenKind::Pound]) {
+                parse_attr(p);
+                continue;
+            }
+
+            if p.at(ITEM_START) || p.at(&[crate::TokenKind::CloseBrace]) {
+                break;
+            }
+
+            parse_pattern(p);
+
+            if p.at(&[crate::TokenKind::Colon]) {
+                p.eat(HighlightGroup::Separator);
+                parse_ty(p);
+            }
+        }
+    }
+
+    if p.at(&[crate::TokenKind::ThinArrow]) {
+        p.eat(HighlightGroup::Separator);
+        parse_ty(p);
+    }
+
+    parse_expr(p, false);
+}
+
+fn parse_member_access(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Dot]));
+    p.eat(HighlightGroup::MemberOper);
+
+    if p.at(&[crate::TokenKind::Await]) {
+        p.eat(HighlightGroup::CtrlFlowKeyword);
+    } else if p.at(&[crate::TokenKind::Ident]) {
+        let member = p.next().unwrap();
+
+        if p.at(&[crate::TokenKind::OpenParen]) {
+            p.output.push(HighlightedSpan {
+                range: member.range,
+                group: HighlightGroup::FunctionCall,
+            });
+
+            parse_tuple(p, false);
+        } else {
+            p.output.push(HighlightedSpan {
+                range: member.range,
+                group: HighlightGroup::MemberUse,
+            });
+        }
+    } else {
+        // Tuple fields are accessed by index.
+        p.push(crate::TokenKind::Int, HighlightGroup::MemberUse);
+    }
+}
+
+fn parse_tuple(p: &mut Parser<'_>, is_pattern: bool) {
     assert!(p.at(&[crate::TokenKind::OpenParen]));
     p.eat(HighlightGroup::Delimiter);
 
@@ -58,6 +387,51 @@ fn parse_tuple(p: &mut Parser, is_pattern: bool) {
             break;
         }
 
+        if p.at(&[crate::TokenKind::Pound]) {
+            parse_attr(p);
+            continue;
+        }
+
+        // The closing delimiter is missing, so we leave whatever closes the enclosing block, or the
+        // item after it, to the block.
+        if p.at(ITEM_START) || p.at(&[crate::TokenKind::CloseBrace]) {
+            break;
+        }
+
+        parse_expr(p, is_pattern);
+    }
+}
+
+// Parses both array expressions (`[a, b]` and `[a; n]`) and slice patterns.
+fn parse_array(p: &mut Parser<'_>, is_pattern: bool) {
+    assert!(p.at(&[crate::TokenKind::OpenBracket]));
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Comma, crate::TokenKind::Semi]) {
+            p.eat(HighlightGroup::Separator);
+        }
+
+        if p.at(&[crate::TokenKind::CloseBracket]) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Pound]) {
+            parse_attr(p);
+            continue;
+        }
+
+        // The closing delimiter is missing, so we leave whatever closes the enclosing block, or the
+        // item after it, to the block.
+        if p.at(ITEM_START) || p.at(&[crate::TokenKind::CloseBrace]) {
+            break;
+        }
+
         parse_expr(p, is_pattern);
     }
 }
@@ -81,6 +455,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_raw_identifier_as_variable() {
+        let mut parser = Parser::new("r#type");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![HighlightedSpan {
+                range: 0..6,
+                group: HighlightGroup::VariableUse,
+            }],
+        );
+    }
+
+    #[test]
+    fn parses_constant_usage() {
+        let mut parser = Parser::new("MAX_LEN");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![HighlightedSpan {
+                range: 0..7,
+                group: HighlightGroup::ConstantUse,
+            }],
+        );
+    }
+
     #[test]
     fn parses_function_call() {
         let mut parser = Parser::new("f()");
@@ -228,4 +630,382 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_range() {
+        let mut parser = Parser::new("1..2");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Number,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_range_without_end() {
+        let mut parser = Parser::new("1..;");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::BinaryOper,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_method_call_on_int() {
+        let mut parser = Parser::new("1.foo()");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 2..5,
+                    group: HighlightGroup::FunctionCall,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_nested_tuple_field_access() {
+        let mut parser = Parser::new("x.0.1");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::MemberUse,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::MemberUse,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_raw_borrow() {
+        let mut parser = Parser::new("&raw const x");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 1..4,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 5..10,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::VariableUse,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_borrow_of_variable_named_raw() {
+        let mut parser = Parser::new("&raw");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 1..4,
+                    group: HighlightGroup::VariableUse,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn reserved_keyword_is_an_error() {
+        let mut parser = Parser::new("yield");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![HighlightedSpan {
+                range: 0..5,
+                group: HighlightGroup::Error,
+            }],
+        );
+    }
+
+    #[test]
+    fn parses_operators() {
+        let mut parser = Parser::new("x += *y? - !z");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 2..4,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::VariableUse,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_closure() {
+        let mut parser = Parser::new("|a, b: u8| a[b]");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 7..9,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_range_pattern_with_binding() {
+        let mut parser = Parser::new("n @ 1..=9");
+        parse_expr(&mut parser, true);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 5..8,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Number,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn lifetime_without_colon_is_not_label() {
+        let mut parser = Parser::new("&'x");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_macro_call() {
+        let mut parser = Parser::new("vec![1, m!(x)]");
+        parse_expr(&mut parser, false);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
 }
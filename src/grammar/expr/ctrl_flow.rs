@@ -0,0 +1,726 @@
+use super::at_expr_end;
+use crate::grammar::{parse_attr, parse_block, parse_expr, ITEM_START};
+use crate::Parser;
+use dialect::HighlightGroup;
+
+pub(super) fn parse_if(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::If]));
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    parse_expr(p, false);
+    parse_body(p);
+
+    if p.at(&[crate::TokenKind::Else]) {
+        p.eat(HighlightGroup::CtrlFlowKeyword);
+
+        if p.at(&[crate::TokenKind::If]) {
+            parse_if(p);
+        } else {
+            parse_body(p);
+        }
+    }
+}
+
+// Parses a `let` in the condition of an `if` or `while`, as in `if let Some(x) = y`. Several of
+// these can be chained together with `&&`, which is handled by parsing them as operands.
+pub(super) fn parse_let(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Let]));
+    p.eat(HighlightGroup::OtherKeyword);
+
+    parse_expr(p, true);
+    p.push(crate::TokenKind::Equals, HighlightGroup::AssignOper);
+    parse_expr(p, false);
+}
+
+pub(super) fn parse_while(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::While]));
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    parse_expr(p, false);
+    parse_body(p);
+}
+
+pub(super) fn parse_loop(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Loop]));
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    parse_body(p);
+}
+
+pub(super) fn parse_for(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::For]));
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    parse_expr(p, true);
+    p.push(crate::TokenKind::In, HighlightGroup::CtrlFlowKeyword);
+    parse_expr(p, false);
+    parse_body(p);
+}
+
+pub(super) fn parse_match(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Match]));
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    parse_expr(p, false);
+
+    if !p.at(&[crate::TokenKind::OpenBrace]) {
+        p.push(crate::TokenKind::OpenBrace, HighlightGroup::Delimiter);
+        return;
+    }
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::CloseBrace]) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Pound]) {
+            parse_attr(p);
+            continue;
+        }
+
+        // The closing delimiter is missing, so we leave the item after it to the enclosing block.
+        if p.at(ITEM_START) {
+            break;
+        }
+
+        parse_match_arm(p);
+    }
+}
+
+fn parse_match_arm(p: &mut Parser<'_>) {
+    // A leading `|` is allowed before the first alternative.
+    if p.at(&[crate::TokenKind::Pipe]) {
+        p.eat(HighlightGroup::BinaryOper);
+    }
+
+    parse_expr(p, true);
+
+    if p.at(&[crate::TokenKind::If]) {
+        p.eat(HighlightGroup::CtrlFlowKeyword);
+        parse_expr(p, false);
+    }
+
+    p.push(crate::TokenKind::FatArrow, HighlightGroup::Separator);
+    parse_expr(p, false);
+
+    if p.at(&[crate::TokenKind::Comma]) {
+        p.eat(HighlightGroup::Separator);
+    }
+}
+
+// Parses a loop or block with a label, as in `'outer: loop {}`.
+pub(super) fn parse_labelled(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::TickIdent]));
+    p.eat(HighlightGroup::VariableDef);
+    p.push(crate::TokenKind::Colon, HighlightGroup::Separator);
+
+    match p.peek().map(|token| &token.kind) {
+        Some(crate::TokenKind::While) => parse_while(p),
+        Some(crate::TokenKind::Loop) => parse_loop(p),
+        Some(crate::TokenKind::For) => parse_for(p),
+        Some(crate::TokenKind::OpenBrace) => parse_block(p),
+        _ => p.push(crate::TokenKind::Loop, HighlightGroup::CtrlFlowKeyword),
+    }
+}
+
+// Parses `return`, `break` and `continue`, all of which can optionally be followed by an expression
+// (although for `continue` this is an error). `break` and `continue` can also name the label of the
+// loop they jump out of.
+pub(super) fn parse_jump(p: &mut Parser<'_>) {
+    assert!(p.at(&[
+        crate::TokenKind::Return,
+        crate::TokenKind::Break,
+        crate::TokenKind::Continue,
+    ]));
+    let is_return = p.at(&[crate::TokenKind::Return]);
+    p.eat(HighlightGroup::CtrlFlowKeyword);
+
+    if !is_return && p.at(&[crate::TokenKind::TickIdent]) {
+        p.eat(HighlightGroup::VariableUse);
+    }
+
+    if !at_expr_end(p) {
+        parse_expr(p, false);
+    }
+}
+
+fn parse_body(p: &mut Parser<'_>) {
+    if p.at(&[crate::TokenKind::OpenBrace]) {
+        parse_block(p);
+    } else {
+        p.push(crate::TokenKind::OpenBrace, HighlightGroup::Delimiter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_if_else_if_else() {
+        let mut parser = Parser::new("if a {} else if b {} else {}");
+        parse_if(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 8..12,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 13..15,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 16..17,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 21..25,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_for_loop() {
+        let mut parser = Parser::new("for i in 0..n {}");
+        parse_for(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 6..8,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 10..12,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_loop_with_break_and_continue() {
+        let mut parser = Parser::new("loop { continue; break true }");
+        parse_loop(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..4,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 7..15,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 17..22,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 23..27,
+                    group: HighlightGroup::Boolean,
+                },
+                HighlightedSpan {
+                    range: 28..29,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_while_loop() {
+        let mut parser = Parser::new("while false {}");
+        parse_while(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..11,
+                    group: HighlightGroup::Boolean,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_match() {
+        let mut parser = Parser::new("match x { 1 | 2 if y => z, _ => {} }");
+        parse_match(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 16..18,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 21..23,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 29..31,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 33..34,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 35..36,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_labelled_loop_with_break_and_continue() {
+        let mut parser = Parser::new("'outer: loop { break 'outer; continue 'outer }");
+        parse_labelled(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 8..12,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..20,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 21..27,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 29..37,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 38..44,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 45..46,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_labelled_block() {
+        let mut parser = Parser::new("'a: { break 'a 1 }");
+        parse_labelled(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 6..11,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 12..14,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_if_let() {
+        let mut parser = Parser::new("if let Some(y) = z {}");
+        parse_if(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..11,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_while_let() {
+        let mut parser = Parser::new("while let Some(x) = it.next() {}");
+        parse_while(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..9,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 10..14,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 16..17,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 20..22,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 23..27,
+                    group: HighlightGroup::FunctionCall,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 28..29,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_if_let_chain() {
+        let mut parser = Parser::new("if let Some(x) = a && x > 1 && let Ok(y) = b {}");
+        parse_if(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CtrlFlowKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..11,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 19..21,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 28..30,
+                    group: HighlightGroup::BinaryOper,
+                },
+                HighlightedSpan {
+                    range: 31..34,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 35..37,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 37..38,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 38..39,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 39..40,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 41..42,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 43..44,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 45..46,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 46..47,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+}
@@ -0,0 +1,595 @@
+use crate::lexer::{scan_escape, Mode};
+use crate::Parser;
+use dialect::{HighlightGroup, HighlightedSpan};
+
+pub(crate) fn parse_literal(p: &mut Parser<'_>) {
+    let literal = p.next().unwrap();
+    let range = literal.range;
+
+    match literal.kind {
+        crate::TokenKind::Int | crate::TokenKind::Float => p.output.push(HighlightedSpan {
+            range,
+            group: HighlightGroup::Number,
+        }),
+        crate::TokenKind::String => parse_quoted(p, range, Mode::Str, HighlightGroup::String),
+        crate::TokenKind::ByteString => {
+            parse_quoted(p, range, Mode::ByteStr, HighlightGroup::String)
+        }
+        crate::TokenKind::CString => parse_quoted(p, range, Mode::CStr, HighlightGroup::String),
+        crate::TokenKind::Char => parse_quoted(p, range, Mode::Char, HighlightGroup::Character),
+        crate::TokenKind::Byte => parse_quoted(p, range, Mode::Byte, HighlightGroup::Character),
+        crate::TokenKind::RawString => parse_raw(p, range, Mode::Str),
+        crate::TokenKind::RawByteString => parse_raw(p, range, Mode::ByteStr),
+        crate::TokenKind::RawCString => parse_raw(p, range, Mode::CStr),
+        _ => unreachable!(),
+    }
+}
+
+// Highlights a literal that is opened by an optional prefix (such as `b`) followed by a quote, and
+// closed by the same quote.
+fn parse_quoted(
+    p: &mut Parser<'_>,
+    range: std::ops::Range<usize>,
+    mode: Mode,
+    group: HighlightGroup,
+) {
+    let delimiter_group = delimiter_group(group);
+    let opening_end = range.start + p.input[range.clone()].find(&['"', '\''][..]).unwrap() + 1;
+
+    p.output.push(HighlightedSpan {
+        range: range.start..opening_end,
+        group: delimiter_group,
+    });
+
+    push_contents(p, opening_end..range.end - 1, mode, group);
+
+    p.output.push(HighlightedSpan {
+        range: range.end - 1..range.end,
+        group: delimiter_group,
+    });
+}
+
+fn parse_raw(p: &mut Parser<'_>, range: std::ops::Range<usize>, mode: Mode) {
+    let text = &p.input[range.clone()];
+
+    // The opening delimiter is the prefix (`r`, `br` or `cr`), followed by the hashes and then the
+    // quote; the closing delimiter is the quote followed by the same number of hashes.
+    let quote_idx = text.find('"').unwrap();
+    let hashes = text[..quote_idx].bytes().filter(|b| *b == b'#').count();
+    let opening_end = range.start + quote_idx + 1;
+    let closing_start = range.end - hashes - 1;
+
+    p.output.push(HighlightedSpan {
+        range: range.start..opening_end,
+        group: HighlightGroup::StringDelimiter,
+    });
+
+    push_text(p, opening_end..closing_start, mode, HighlightGroup::String);
+
+    p.output.push(HighlightedSpan {
+        range: closing_start..range.end,
+        group: HighlightGroup::StringDelimiter,
+    });
+}
+
+fn delimiter_group(group: HighlightGroup) -> HighlightGroup {
+    match group {
+        HighlightGroup::Character => HighlightGroup::CharacterDelimiter,
+        _ => HighlightGroup::StringDelimiter,
+    }
+}
+
+// Highlights the contents of a literal, giving each escape its own span so that it stands out from
+// the text around it.
+fn push_contents(
+    p: &mut Parser<'_>,
+    range: std::ops::Range<usize>,
+    mode: Mode,
+    group: HighlightGroup,
+) {
+    let mut text_start = range.start;
+    let mut idx = range.start;
+
+    while idx < range.end {
+        if p.input.as_bytes()[idx] != b'\\' {
+            idx += 1;
+            continue;
+        }
+
+        push_text(p, text_start..idx, mode, group);
+
+        let escape = scan_escape(&p.input[idx..range.end], mode);
+
+        p.output.push(HighlightedSpan {
+            range: idx..idx + escape.len,
+            group: if escape.is_valid {
+                HighlightGroup::Character
+            } else {
+                HighlightGroup::Error
+            },
+        });
+
+        idx += escape.len;
+        text_start = idx;
+    }
+
+    push_text(p, text_start..range.end, mode, group);
+}
+
+// Highlights text without escapes, marking any characters that aren’t allowed in this kind of
+// literal (such as non-ASCII characters in byte strings) as errors.
+fn push_text(p: &mut Parser<'_>, range: std::ops::Range<usize>, mode: Mode, group: HighlightGroup) {
+    let mut run_start = range.start;
+    let mut run_is_valid = true;
+
+    for (idx, c) in p.input[range.clone()].char_indices() {
+        let idx = range.start + idx;
+        let is_valid = mode.allows_char(c);
+
+        if is_valid != run_is_valid {
+            push_run(p, run_start..idx, run_is_valid, group);
+            run_start = idx;
+            run_is_valid = is_valid;
+        }
+    }
+
+    push_run(p, run_start..range.end, run_is_valid, group);
+}
+
+fn push_run(
+    p: &mut Parser<'_>,
+    range: std::ops::Range<usize>,
+    is_valid: bool,
+    group: HighlightGroup,
+) {
+    if range.start < range.end {
+        p.output.push(HighlightedSpan {
+            range,
+            group: if is_valid {
+                group
+            } else {
+                HighlightGroup::Error
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_string() {
+        let mut parser = Parser::new(r#""foo""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..4,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_empty_string() {
+        let mut parser = Parser::new(r#""""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_string_with_escapes() {
+        let mut parser = Parser::new(r#""a\nb\x7f\u{1F600}""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 2..4,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 5..9,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 9..18,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_string_with_line_continuation() {
+        let mut parser = Parser::new("\"a\\\n  b\"");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 2..6,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn malformed_escapes_are_errors() {
+        let mut parser = Parser::new(r#""\q\x80""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 3..7,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_raw_string() {
+        let mut parser = Parser::new(r###"r##"a "# \n b"##"###);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..4,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 4..13,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 13..16,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_empty_raw_string() {
+        let mut parser = Parser::new(r#"r"""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_byte() {
+        let mut parser = Parser::new(r"b'\xff'");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..6,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn non_ascii_byte_is_error() {
+        let mut parser = Parser::new("b'é'");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..4,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn unicode_escape_in_byte_is_error() {
+        let mut parser = Parser::new(r"b'\u{41}'");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..8,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_byte_string_with_non_ascii_chars() {
+        let mut parser = Parser::new(r#"b"a\tßc""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 3..5,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_raw_byte_string() {
+        let mut parser = Parser::new(r##"br#"a"#"##);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..4,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_c_string_with_nul_escape_as_error() {
+        let mut parser = Parser::new(r#"c"é\0""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 2..4,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 4..6,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_raw_c_string() {
+        let mut parser = Parser::new(r#"cr"a""#);
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::StringDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_char() {
+        let mut parser = Parser::new("'é'");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::Character,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_char_with_invalid_escape() {
+        let mut parser = Parser::new(r"'\q'");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::CharacterDelimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_int() {
+        let mut parser = Parser::new("0xFFi64");
+        parse_literal(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![HighlightedSpan {
+                range: 0..7,
+                group: HighlightGroup::Number,
+            }],
+        );
+    }
+}
@@ -1,16 +1,41 @@
+mod const_;
 mod fn_;
+mod macro_;
 mod struct_;
 mod trait_;
+mod union_;
 
+use crate::grammar::{parse_attr, NAME};
 use crate::Parser;
+use const_::parse_const;
 use dialect::HighlightGroup;
 use fn_::parse_fn;
+pub(crate) use macro_::parse_macro_call;
+use macro_::parse_macro_rules;
 use struct_::parse_struct;
 use trait_::parse_trait;
+use union_::parse_union;
 
-pub(crate) fn parse_item(p: &mut Parser) {
+/// The tokens that can begin an item. Parsers use this to recover: when they come across one of
+/// these where they expected something else, they stop so that the item can be parsed properly.
+pub(crate) const ITEM_START: &[crate::TokenKind] = &[
+    crate::TokenKind::Pound,
+    crate::TokenKind::Pub,
+    crate::TokenKind::Fn,
+    crate::TokenKind::Struct,
+    crate::TokenKind::Trait,
+    crate::TokenKind::Const,
+    crate::TokenKind::Static,
+];
+
+pub(crate) fn parse_item(p: &mut Parser<'_>) {
     while let Some(token) = p.peek() {
         match token.kind {
+            // Visibility is parsed on its own, after which we carry on looking for the item.
+            crate::TokenKind::Pub => parse_visibility(p),
+            // So are attributes.
+            crate::TokenKind::Pound => parse_attr(p),
+
             crate::TokenKind::Fn => {
                 parse_fn(p);
                 break;
@@ -24,6 +49,35 @@ pub(crate) fn parse_item(p: &mut Parser) {
                 break;
             }
 
+            // `const` is a qualifier in `const fn`, as well as introducing a constant.
+            crate::TokenKind::Const
+                if p.nth_at(1, &[crate::TokenKind::Fn, crate::TokenKind::Unsafe]) =>
+            {
+                p.eat(HighlightGroup::OtherKeyword)
+            }
+            crate::TokenKind::Const | crate::TokenKind::Static => {
+                parse_const(p);
+                break;
+            }
+
+            // Contextual keywords are only keywords where an item could start with them; anywhere
+            // else they are plain identifiers.
+            crate::TokenKind::Ident if p.nth_at_contextual_kw(0, "union") => {
+                if p.nth_at(1, NAME) {
+                    parse_union(p);
+                    break;
+                }
+                p.eat(HighlightGroup::Error);
+            }
+            crate::TokenKind::Ident if p.nth_at_contextual_kw(0, "macro_rules") => {
+                if p.nth_at(1, &[crate::TokenKind::Bang]) {
+                    parse_macro_rules(p);
+                    break;
+                }
+                p.eat(HighlightGroup::Error);
+            }
+            crate::TokenKind::Ident if at_item_qualifier(p) => p.eat(HighlightGroup::OtherKeyword),
+
             crate::TokenKind::CloseBrace => {
                 break;
             }
@@ -32,6 +86,62 @@ pub(crate) fn parse_item(p: &mut Parser) {
     }
 }
 
+/// Whether an item starts here, including those that start with a contextual keyword (such as
+/// `union U {}` and `macro_rules! m {}`) -- unlike `ITEM_START`, this needs to look ahead.
+pub(crate) fn at_item_start(p: &Parser<'_>) -> bool {
+    p.at(ITEM_START)
+        || p.nth_at_contextual_kw(0, "union") && p.nth_at(1, NAME)
+        || p.nth_at_contextual_kw(0, "macro_rules") && p.nth_at(1, &[crate::TokenKind::Bang])
+        || at_item_qualifier(p)
+}
+
+// Whether we’re at one of the contextual keywords that qualify the item after them, as in
+// `auto trait`, `default fn` and `safe fn`.
+fn at_item_qualifier(p: &Parser<'_>) -> bool {
+    ["auto", "default", "safe"]
+        .iter()
+        .any(|kw| p.nth_at_contextual_kw(0, kw))
+        && p.nth_at(
+            1,
+            &[
+                crate::TokenKind::Fn,
+                crate::TokenKind::Trait,
+                crate::TokenKind::Unsafe,
+                crate::TokenKind::Impl,
+                crate::TokenKind::Const,
+                crate::TokenKind::Static,
+                crate::TokenKind::Type,
+            ],
+        )
+}
+
+// Parses `pub`, `pub(crate)`, `pub(super)`, `pub(self)` and `pub(in path)`.
+fn parse_visibility(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Pub]));
+    p.eat(HighlightGroup::OtherKeyword);
+
+    if !p.at(&[crate::TokenKind::OpenParen]) {
+        return;
+    }
+    p.eat(HighlightGroup::Delimiter);
+
+    while let Some(token) = p.peek() {
+        match token.kind {
+            crate::TokenKind::CloseParen => {
+                p.eat(HighlightGroup::Delimiter);
+                break;
+            }
+            crate::TokenKind::Crate
+            | crate::TokenKind::Super
+            | crate::TokenKind::SelfValue
+            | crate::TokenKind::In => p.eat(HighlightGroup::OtherKeyword),
+            crate::TokenKind::Ident => p.eat(HighlightGroup::ModuleUse),
+            crate::TokenKind::DoubleColon => p.eat(HighlightGroup::MemberOper),
+            _ => break,
+        }
+    }
+}
+
 // Most of these tests just check if each item’s parser has been hooked in correctly by looking at
 // the first HighlightedSpan found, rather than checking the entire parser output.
 #[cfg(test)]
@@ -105,4 +215,141 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_visibility_before_item() {
+        let mut parser = Parser::new("pub(in crate::a) struct A;");
+        parse_item(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 4..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..12,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 12..14,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::ModuleUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 17..23,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn unexpected_keywords_are_still_highlighted_as_keywords() {
+        let mut parser = Parser::new("impl fn");
+        parse_item(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..4,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::OtherKeyword,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_union() {
+        test("union U { a: A }", HighlightGroup::OtherKeyword);
+    }
+
+    #[test]
+    fn parses_macro_rules() {
+        test("macro_rules! m {}", HighlightGroup::MacroUse);
+    }
+
+    #[test]
+    fn parses_auto_trait() {
+        test("auto trait T {}", HighlightGroup::OtherKeyword);
+    }
+
+    #[test]
+    fn contextual_keyword_is_not_an_item_on_its_own() {
+        test("union;", HighlightGroup::Error);
+    }
+
+    #[test]
+    fn parses_const() {
+        test("const A: B = C;", HighlightGroup::OtherKeyword);
+    }
+
+    #[test]
+    fn parses_const_fn() {
+        let mut parser = Parser::new("const fn f() {}");
+        parse_item(&mut parser);
+
+        assert_eq!(
+            &parser.output[..2],
+            &[
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..8,
+                    group: HighlightGroup::OtherKeyword,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_attribute_before_item() {
+        let mut parser = Parser::new("#[a] fn b() {}");
+        parse_item(&mut parser);
+
+        assert_eq!(
+            &parser.output[4..6],
+            &[
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::FunctionDef,
+                },
+            ],
+        );
+    }
 }
@@ -0,0 +1,127 @@
+use crate::grammar::{parse_expr, parse_ty};
+use crate::Parser;
+use dialect::HighlightGroup;
+
+// Parses both `const` and `static` items, which only differ in that statics can be `mut`.
+pub(super) fn parse_const(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Const, crate::TokenKind::Static]));
+    let is_static = p.at(&[crate::TokenKind::Static]);
+    p.eat(HighlightGroup::OtherKeyword);
+
+    if is_static && p.at(&[crate::TokenKind::Mut]) {
+        p.eat(HighlightGroup::OtherKeyword);
+    }
+
+    // Whatever the name looks like, it’s defined as a constant.
+    if p.at(&[
+        crate::TokenKind::ConstIdent,
+        crate::TokenKind::TypeIdent,
+        crate::TokenKind::Ident,
+        crate::TokenKind::Underscore,
+    ]) {
+        p.eat(HighlightGroup::ConstantDef);
+    } else {
+        p.push(crate::TokenKind::ConstIdent, HighlightGroup::ConstantDef);
+    }
+
+    p.push(crate::TokenKind::Colon, HighlightGroup::Separator);
+    parse_ty(p);
+
+    if p.at(&[crate::TokenKind::Equals]) {
+        p.eat(HighlightGroup::AssignOper);
+        parse_expr(p, false);
+    }
+
+    p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_const() {
+        let mut parser = Parser::new("const MAX_LEN: Len = DEFAULT_LEN;");
+        parse_const(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..13,
+                    group: HighlightGroup::ConstantDef,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 15..18,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 21..32,
+                    group: HighlightGroup::ConstantUse,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_static_mut() {
+        let mut parser = Parser::new("static mut COUNT: Count = 0;");
+        parse_const(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..10,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 11..16,
+                    group: HighlightGroup::ConstantDef,
+                },
+                HighlightedSpan {
+                    range: 16..17,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 18..23,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+}
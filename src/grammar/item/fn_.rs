@@ -1,34 +1,28 @@
-use crate::grammar::parse_block;
+use crate::grammar::{parse_block, parse_generic_params, parse_name, parse_ty};
 use crate::Parser;
-use dialect::{HighlightGroup, HighlightedSpan};
+use dialect::HighlightGroup;
 
-pub(super) fn parse_fn(p: &mut Parser) {
+pub(super) fn parse_fn(p: &mut Parser<'_>) {
     assert!(p.at(&[crate::TokenKind::Fn]));
     p.eat(HighlightGroup::OtherKeyword);
 
-    p.push(crate::TokenKind::Ident, HighlightGroup::FunctionDef);
+    parse_name(p, HighlightGroup::FunctionDef);
+
+    if p.at(&[crate::TokenKind::Less]) {
+        parse_generic_params(p);
+    }
+
     p.push(crate::TokenKind::OpenParen, HighlightGroup::Delimiter);
     p.push(crate::TokenKind::CloseParen, HighlightGroup::Delimiter);
 
     if p.at(&[crate::TokenKind::ThinArrow]) {
-        let thin_arrow = p.next().unwrap();
-
-        if p.at(&[crate::TokenKind::TypeIdent]) {
-            let type_ = p.next().unwrap();
-            p.output.push(HighlightedSpan {
-                range: thin_arrow.range,
-                group: HighlightGroup::Separator,
-            });
-            p.output.push(HighlightedSpan {
-                range: type_.range,
-                group: HighlightGroup::TyUse,
-            });
+        // If we don’t have a type after the arrow, then the arrow is parsed as an error.
+        if p.nth_at(1, &[crate::TokenKind::OpenBrace, crate::TokenKind::Semi]) || p.nth(1).is_none()
+        {
+            p.eat(HighlightGroup::Error);
         } else {
-            // If we don’t have a type after the arrow, then the arrow is parsed as an error.
-            p.output.push(HighlightedSpan {
-                range: thin_arrow.range,
-                group: HighlightGroup::Error,
-            });
+            p.eat(HighlightGroup::Separator);
+            parse_ty(p);
         }
     }
 
@@ -36,6 +30,7 @@ pub(super) fn parse_fn(p: &mut Parser) {
         match token.kind {
             crate::TokenKind::Semi => p.eat(HighlightGroup::Terminator),
             crate::TokenKind::OpenBrace => parse_block(p),
+            _ if p.at(crate::grammar::ITEM_START) => {}
             _ => p.eat(HighlightGroup::Error),
         }
     }
@@ -44,6 +39,7 @@ pub(super) fn parse_fn(p: &mut Parser) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use dialect::HighlightedSpan;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -197,4 +193,160 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_fn_with_generic_params() {
+        let mut parser = Parser::new("fn f<'a, T>() {}");
+        parse_fn(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_return_type_with_lifetime() {
+        let mut parser = Parser::new("fn f<'a>() -> &'a str {}");
+        parse_fn(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..13,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 15..17,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 18..21,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 23..24,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_fn_with_ffi_style_name() {
+        let mut parser = Parser::new("fn GET() {}");
+        parse_fn(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..6,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
 }
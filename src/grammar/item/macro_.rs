@@ -0,0 +1,606 @@
+use crate::grammar::{parse_literal, parse_name};
+use crate::Parser;
+use dialect::HighlightGroup;
+
+const OPEN_DELIMITERS: &[crate::TokenKind] = &[
+    crate::TokenKind::OpenParen,
+    crate::TokenKind::OpenBrace,
+    crate::TokenKind::OpenBracket,
+];
+
+const CLOSE_DELIMITERS: &[crate::TokenKind] = &[
+    crate::TokenKind::CloseParen,
+    crate::TokenKind::CloseBrace,
+    crate::TokenKind::CloseBracket,
+];
+
+const REPETITION_OPERATORS: &[crate::TokenKind] = &[
+    crate::TokenKind::Star,
+    crate::TokenKind::Plus,
+    crate::TokenKind::Question,
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Part {
+    Matcher,
+    Transcriber,
+}
+
+pub(super) fn parse_macro_rules(p: &mut Parser<'_>) {
+    assert!(p.nth_at_contextual_kw(0, "macro_rules"));
+    p.eat(HighlightGroup::MacroUse);
+    p.push(crate::TokenKind::Bang, HighlightGroup::MacroUse);
+    parse_name(p, HighlightGroup::MacroDef);
+
+    if p.at(&[crate::TokenKind::OpenParen, crate::TokenKind::OpenBracket]) {
+        parse_rules(p);
+        p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
+    } else if p.at(&[crate::TokenKind::OpenBrace]) {
+        parse_rules(p);
+    } else {
+        p.push(crate::TokenKind::OpenBrace, HighlightGroup::Delimiter);
+    }
+}
+
+/// Parses a macro invocation where an expression or pattern is expected, as in
+/// `println!("{}", x)`. Its arguments are parsed like the transcriber of a rule, since we can’t
+/// know what they mean to the macro either.
+pub(crate) fn parse_macro_call(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Ident]) && p.nth_at(1, &[crate::TokenKind::Bang]));
+    p.eat(HighlightGroup::MacroUse);
+    p.eat(HighlightGroup::MacroUse);
+
+    if p.at(OPEN_DELIMITERS) {
+        parse_token_tree(p, Part::Transcriber);
+    } else {
+        p.push(crate::TokenKind::OpenParen, HighlightGroup::Delimiter);
+    }
+}
+
+// Each rule is a matcher and a transcriber separated by `=>`, and the rules are separated by `;`.
+fn parse_rules(p: &mut Parser<'_>) {
+    assert!(p.at(OPEN_DELIMITERS));
+    p.eat(HighlightGroup::Delimiter);
+
+    while let Some(token) = p.peek() {
+        match token.kind {
+            crate::TokenKind::CloseParen
+            | crate::TokenKind::CloseBrace
+            | crate::TokenKind::CloseBracket => {
+                p.eat(HighlightGroup::Delimiter);
+                break;
+            }
+            crate::TokenKind::OpenParen
+            | crate::TokenKind::OpenBrace
+            | crate::TokenKind::OpenBracket => {
+                parse_token_tree(p, Part::Matcher);
+
+                if p.at(&[crate::TokenKind::FatArrow]) {
+                    p.eat(HighlightGroup::Separator);
+                }
+
+                if p.at(OPEN_DELIMITERS) {
+                    parse_token_tree(p, Part::Transcriber);
+                }
+            }
+            crate::TokenKind::Semi => p.eat(HighlightGroup::Terminator),
+            _ => p.eat(HighlightGroup::Error),
+        }
+    }
+}
+
+// The rules of a macro aren’t parsed as Rust code, so apart from metavariables and repetitions all
+// we do is find the delimiter that closes the one we’re at, highlighting each token in between on
+// its own.
+fn parse_token_tree(p: &mut Parser<'_>, part: Part) {
+    assert!(p.at(OPEN_DELIMITERS));
+    p.eat(HighlightGroup::Delimiter);
+
+    let mut depth = 1;
+
+    while let Some(token) = p.peek() {
+        match token.kind {
+            crate::TokenKind::MetaVar => parse_metavar(p, part),
+            crate::TokenKind::Dollar if p.nth_at(1, &[crate::TokenKind::OpenParen]) => {
+                parse_repetition(p, part)
+            }
+            crate::TokenKind::OpenParen
+            | crate::TokenKind::OpenBrace
+            | crate::TokenKind::OpenBracket => {
+                depth += 1;
+                p.eat(HighlightGroup::Delimiter);
+            }
+            crate::TokenKind::CloseParen
+            | crate::TokenKind::CloseBrace
+            | crate::TokenKind::CloseBracket => {
+                depth -= 1;
+                p.eat(HighlightGroup::Delimiter);
+
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => parse_other_token(p),
+        }
+    }
+}
+
+// We don’t know what the tokens in a macro will end up being, so they are highlighted based only
+// on what they look like.
+fn parse_other_token(p: &mut Parser<'_>) {
+    let kind = p.peek().unwrap().kind.clone();
+
+    if let Some(group) = kind.keyword_group() {
+        p.eat(group);
+        return;
+    }
+
+    match kind {
+        crate::TokenKind::Int
+        | crate::TokenKind::Float
+        | crate::TokenKind::String
+        | crate::TokenKind::RawString
+        | crate::TokenKind::Char
+        | crate::TokenKind::Byte
+        | crate::TokenKind::ByteString
+        | crate::TokenKind::RawByteString
+        | crate::TokenKind::CString
+        | crate::TokenKind::RawCString => parse_literal(p),
+
+        crate::TokenKind::Ident => p.eat(HighlightGroup::VariableUse),
+        crate::TokenKind::TypeIdent => p.eat(HighlightGroup::TyUse),
+        crate::TokenKind::ConstIdent => p.eat(HighlightGroup::ConstantUse),
+        crate::TokenKind::TickIdent => p.eat(HighlightGroup::SpecialIdentUse),
+
+        crate::TokenKind::DoubleColon | crate::TokenKind::Dot => p.eat(HighlightGroup::MemberOper),
+        crate::TokenKind::Comma | crate::TokenKind::Semi => p.eat(HighlightGroup::Separator),
+        crate::TokenKind::Error => p.eat(HighlightGroup::Error),
+        _ => p.eat(HighlightGroup::OtherOper),
+    }
+}
+
+// Metavariables are declared in the matcher along with the kind of fragment they match (as in
+// `$x:expr`), and used in the transcriber. `$crate` is always a use, since it refers to the crate
+// the macro was defined in.
+fn parse_metavar(p: &mut Parser<'_>, part: Part) {
+    assert!(p.at(&[crate::TokenKind::MetaVar]));
+
+    let is_crate = p
+        .peek()
+        .map_or(false, |token| &p.input[token.range.clone()] == "$crate");
+
+    if is_crate || part == Part::Transcriber {
+        p.eat(HighlightGroup::SpecialIdentUse);
+        return;
+    }
+
+    p.eat(HighlightGroup::SpecialIdentDef);
+
+    if p.at(&[crate::TokenKind::Colon]) {
+        p.eat(HighlightGroup::Separator);
+
+        if at_fragment_specifier(p) {
+            p.eat(HighlightGroup::PrimitiveTy);
+        } else if !p.at(CLOSE_DELIMITERS) {
+            p.eat(HighlightGroup::Error);
+        }
+    }
+}
+
+fn at_fragment_specifier(p: &Parser<'_>) -> bool {
+    const FRAGMENT_SPECIFIERS: &[&str] = &[
+        "block",
+        "expr",
+        "expr_2021",
+        "ident",
+        "item",
+        "lifetime",
+        "literal",
+        "meta",
+        "pat",
+        "pat_param",
+        "path",
+        "stmt",
+        "tt",
+        "ty",
+        "vis",
+    ];
+
+    FRAGMENT_SPECIFIERS
+        .iter()
+        .any(|specifier| p.nth_at_contextual_kw(0, specifier))
+}
+
+// A repetition such as `$($x:expr),*` repeats what’s inside the parentheses, optionally separated
+// by a token (here `,`), as many times as the operator after it allows.
+fn parse_repetition(p: &mut Parser<'_>, part: Part) {
+    assert!(p.at(&[crate::TokenKind::Dollar]));
+    p.eat(HighlightGroup::OtherOper);
+
+    parse_token_tree(p, part);
+
+    let at_separator = !p.at(REPETITION_OPERATORS)
+        && !p.at(OPEN_DELIMITERS)
+        && !p.at(CLOSE_DELIMITERS)
+        && p.nth_at(1, REPETITION_OPERATORS);
+
+    if at_separator {
+        p.eat(HighlightGroup::Separator);
+    }
+
+    if p.at(REPETITION_OPERATORS) {
+        p.eat(HighlightGroup::OtherOper);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_macro_rules() {
+        let mut parser = Parser::new("macro_rules! m { () => { x } }");
+        parse_macro_rules(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..11,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::MacroDef,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 20..22,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 23..24,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 29..30,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_metavariables_and_repetitions() {
+        let mut parser = Parser::new("macro_rules! v { ($($x:expr),*) => { $crate::f($($x)+) }; }");
+        parse_macro_rules(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..11,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::MacroDef,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 20..22,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 23..27,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 28..29,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 29..30,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 32..34,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 35..36,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 37..43,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 43..45,
+                    group: HighlightGroup::MemberOper,
+                },
+                HighlightedSpan {
+                    range: 45..46,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 46..47,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 47..48,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 48..49,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 49..51,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 51..52,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 52..53,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 53..54,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 55..56,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 56..57,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 58..59,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn flags_unknown_fragment_specifier() {
+        let mut parser = Parser::new("macro_rules! m(($x:foo) => [$x]);");
+        parse_macro_rules(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..11,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::MacroDef,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 16..18,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 19..22,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 24..26,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 28..30,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn highlights_other_tokens_in_rules() {
+        let mut parser =
+            Parser::new(r#"macro_rules! m { ($p:pat_param | $i:ident) => { let x = "}"; } }"#);
+        parse_macro_rules(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..11,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::MacroUse,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::MacroDef,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 18..20,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 21..30,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 33..35,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 35..36,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 36..41,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 41..42,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 43..45,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 46..47,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 48..51,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 52..53,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 54..55,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 56..57,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 57..58,
+                    group: HighlightGroup::String,
+                },
+                HighlightedSpan {
+                    range: 58..59,
+                    group: HighlightGroup::StringDelimiter,
+                },
+                HighlightedSpan {
+                    range: 59..60,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 61..62,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 63..64,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+}
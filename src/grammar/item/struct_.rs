@@ -1,12 +1,62 @@
+use super::parse_visibility;
+use crate::grammar::{parse_attr, parse_generic_params, parse_name, parse_ty, NAME};
 use crate::Parser;
 use dialect::HighlightGroup;
 
-pub(super) fn parse_struct(p: &mut Parser) {
+pub(super) fn parse_struct(p: &mut Parser<'_>) {
     assert!(p.at(&[crate::TokenKind::Struct]));
     p.eat(HighlightGroup::OtherKeyword);
 
-    p.push(crate::TokenKind::TypeIdent, HighlightGroup::TyDef);
-    p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
+    parse_name(p, HighlightGroup::TyDef);
+
+    if p.at(&[crate::TokenKind::Less]) {
+        parse_generic_params(p);
+    }
+
+    if p.at(&[crate::TokenKind::OpenBrace]) {
+        parse_record_fields(p);
+    } else {
+        p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
+    }
+}
+
+/// Parses the fields of a struct or union with named fields, including the braces.
+pub(super) fn parse_record_fields(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::OpenBrace]));
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::CloseBrace]) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Pound]) {
+            parse_attr(p);
+            continue;
+        }
+
+        if p.at(&[crate::TokenKind::Pub]) {
+            parse_visibility(p);
+        }
+
+        if !p.at(NAME) {
+            p.eat(HighlightGroup::Error);
+            continue;
+        }
+
+        p.eat(HighlightGroup::MemberDef);
+        p.push(crate::TokenKind::Colon, HighlightGroup::Separator);
+        parse_ty(p);
+
+        if p.at(&[crate::TokenKind::Comma]) {
+            p.eat(HighlightGroup::Separator);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -38,4 +88,128 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_struct_with_named_fields() {
+        let mut parser = Parser::new("struct T { a: A, pub b: B }");
+        parse_struct(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::MemberDef,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 17..20,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 21..22,
+                    group: HighlightGroup::MemberDef,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_struct_with_ffi_style_names() {
+        let mut parser = Parser::new("struct sockaddr_in { CAPS: u8 }");
+        parse_struct(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..18,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 21..25,
+                    group: HighlightGroup::MemberDef,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 27..29,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_struct_with_all_caps_name() {
+        let mut parser = Parser::new("struct IO;");
+        parse_struct(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..9,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
 }
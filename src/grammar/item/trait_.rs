@@ -1,12 +1,16 @@
-use crate::grammar::parse_item;
+use crate::grammar::{parse_generic_params, parse_item, parse_name};
 use crate::Parser;
 use dialect::HighlightGroup;
 
-pub(super) fn parse_trait(p: &mut Parser) {
+pub(super) fn parse_trait(p: &mut Parser<'_>) {
     assert!(p.at(&[crate::TokenKind::Trait]));
     p.eat(HighlightGroup::OtherKeyword);
 
-    p.push(crate::TokenKind::TypeIdent, HighlightGroup::InterfaceDef);
+    parse_name(p, HighlightGroup::InterfaceDef);
+
+    if p.at(&[crate::TokenKind::Less]) {
+        parse_generic_params(p);
+    }
 
     p.push(crate::TokenKind::OpenBrace, HighlightGroup::Delimiter);
 
@@ -165,4 +169,32 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn parses_trait_with_ffi_style_name() {
+        let mut parser = Parser::new("trait myTrait {}");
+        parse_trait(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..13,
+                    group: HighlightGroup::InterfaceDef,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
 }
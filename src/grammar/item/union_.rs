@@ -0,0 +1,68 @@
+use super::struct_::parse_record_fields;
+use crate::grammar::{parse_generic_params, parse_name};
+use crate::Parser;
+use dialect::HighlightGroup;
+
+pub(super) fn parse_union(p: &mut Parser<'_>) {
+    assert!(p.nth_at_contextual_kw(0, "union"));
+    p.eat(HighlightGroup::OtherKeyword);
+
+    parse_name(p, HighlightGroup::TyDef);
+
+    if p.at(&[crate::TokenKind::Less]) {
+        parse_generic_params(p);
+    }
+
+    if p.at(&[crate::TokenKind::OpenBrace]) {
+        parse_record_fields(p);
+    } else {
+        p.push(crate::TokenKind::OpenBrace, HighlightGroup::Delimiter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_union() {
+        let mut parser = Parser::new("union U { a: A }");
+        parse_union(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..7,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::MemberDef,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+}
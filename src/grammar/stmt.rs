@@ -1,8 +1,8 @@
-use crate::grammar::parse_expr;
+use crate::grammar::{parse_expr, parse_ty, BLOCK_LIKE_START};
 use crate::Parser;
 use dialect::HighlightGroup;
 
-pub(crate) fn parse_stmt(p: &mut Parser) {
+pub(crate) fn parse_stmt(p: &mut Parser<'_>) {
     match p.peek() {
         Some(crate::Token {
             kind: crate::TokenKind::Let,
@@ -11,17 +11,29 @@ pub(crate) fn parse_stmt(p: &mut Parser) {
             p.eat(HighlightGroup::OtherKeyword);
 
             parse_expr(p, true);
+
+            if p.at(&[crate::TokenKind::Colon]) {
+                p.eat(HighlightGroup::Separator);
+                parse_ty(p);
+            }
+
             p.push(crate::TokenKind::Equals, HighlightGroup::AssignOper);
             parse_expr(p, false);
 
             p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
         }
         _ => {
+            // A label can only come before a loop or block.
+            let is_block_like = p.at(BLOCK_LIKE_START)
+                || p.at(&[crate::TokenKind::TickIdent]) && p.nth_at(1, &[crate::TokenKind::Colon]);
             parse_expr(p, false);
 
             // Only parse semicolon if the next token is not a close brace -- if it is, then
             // that means we are at the end of a block and as such don’t require a semicolon.
-            if !p.at(&[crate::TokenKind::CloseBrace]) {
+            // Expressions ending in a block don’t need one either.
+            if p.at(&[crate::TokenKind::Semi]) {
+                p.eat(HighlightGroup::Terminator);
+            } else if !p.at(&[crate::TokenKind::CloseBrace]) && !is_block_like {
                 p.push(crate::TokenKind::Semi, HighlightGroup::Terminator);
             }
         }
@@ -65,4 +77,140 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn contextual_keyword_can_be_used_as_variable() {
+        let mut parser = Parser::new("let union = 1;");
+        parse_stmt(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 4..9,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn splits_operator_closing_generic_args_in_let_type() {
+        let mut parser = Parser::new("let v: Vec<Vec<u8>>= w;");
+        parse_stmt(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 7..10,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..14,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..17,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 21..22,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_let_with_all_caps_type() {
+        let mut parser = Parser::new("let x: HTTP = y;");
+        parse_stmt(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::VariableDef,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 7..11,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::AssignOper,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::VariableUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
 }
@@ -0,0 +1,646 @@
+use crate::grammar::parse_expr;
+use crate::Parser;
+use dialect::HighlightGroup;
+
+pub(crate) fn parse_ty(p: &mut Parser<'_>) {
+    if let Some(token) = p.peek() {
+        match token.kind {
+            crate::TokenKind::Ident if at_primitive_ty(p) => p.eat(HighlightGroup::PrimitiveTy),
+            // Any identifier in type position names a type, whatever its case, as in `sockaddr_in`
+            // or `FILE`.
+            crate::TokenKind::TypeIdent
+            | crate::TokenKind::Ident
+            | crate::TokenKind::ConstIdent => {
+                p.eat(HighlightGroup::TyUse);
+
+                if p.at(&[crate::TokenKind::Less]) {
+                    parse_generic_args(p);
+                }
+            }
+            crate::TokenKind::SelfType => p.eat(HighlightGroup::OtherKeyword),
+            crate::TokenKind::OpenParen => parse_tuple_ty(p),
+            crate::TokenKind::OpenBracket => parse_array_ty(p),
+            crate::TokenKind::And | crate::TokenKind::AndAnd => {
+                p.eat(HighlightGroup::PointerOper);
+
+                if p.at(&[crate::TokenKind::TickIdent]) {
+                    parse_lifetime(p, false);
+                }
+
+                if p.at(&[crate::TokenKind::Mut]) {
+                    p.eat(HighlightGroup::OtherKeyword);
+                }
+
+                parse_ty(p);
+            }
+            crate::TokenKind::Star => {
+                p.eat(HighlightGroup::PointerOper);
+
+                if p.at(&[crate::TokenKind::Const, crate::TokenKind::Mut]) {
+                    p.eat(HighlightGroup::OtherKeyword);
+                } else {
+                    p.push(crate::TokenKind::Const, HighlightGroup::OtherKeyword);
+                }
+
+                parse_ty(p);
+            }
+            // The never type.
+            crate::TokenKind::Bang => p.eat(HighlightGroup::PrimitiveTy),
+            crate::TokenKind::Underscore => p.eat(HighlightGroup::OtherKeyword),
+            _ => p.eat(HighlightGroup::Error),
+        }
+    }
+}
+
+fn at_primitive_ty(p: &Parser<'_>) -> bool {
+    const PRIMITIVE_TYS: &[&str] = &[
+        "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32",
+        "i64", "i128", "isize", "f32", "f64",
+    ];
+
+    PRIMITIVE_TYS.iter().any(|ty| p.nth_at_contextual_kw(0, ty))
+}
+
+fn parse_generic_args(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Less]));
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Comma]) {
+            p.eat(HighlightGroup::Separator);
+        }
+
+        if at_generics_end(p) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::TickIdent]) {
+            parse_lifetime(p, false);
+        } else {
+            parse_ty(p);
+        }
+    }
+}
+
+/// Parses the generic parameters of an item, as in `fn f<'a, T: Clone + 'a, const N: usize>`.
+pub(crate) fn parse_generic_params(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::Less]));
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Comma]) {
+            p.eat(HighlightGroup::Separator);
+        }
+
+        if at_generics_end(p) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        match p.peek().map(|token| &token.kind) {
+            Some(crate::TokenKind::TickIdent) => {
+                parse_lifetime(p, true);
+
+                if p.at(&[crate::TokenKind::Colon]) {
+                    p.eat(HighlightGroup::Separator);
+                    parse_bounds(p);
+                }
+            }
+            Some(
+                crate::TokenKind::TypeIdent
+                | crate::TokenKind::Ident
+                | crate::TokenKind::ConstIdent,
+            ) => {
+                p.eat(HighlightGroup::TyDef);
+
+                if p.at(&[crate::TokenKind::Colon]) {
+                    p.eat(HighlightGroup::Separator);
+                    parse_bounds(p);
+                }
+
+                if p.at(&[crate::TokenKind::Equals]) {
+                    p.eat(HighlightGroup::AssignOper);
+                    parse_ty(p);
+                }
+            }
+            Some(crate::TokenKind::Const) => {
+                p.eat(HighlightGroup::OtherKeyword);
+
+                // Const parameters are usually a single capital letter, which is lexed as a type.
+                if p.at(&[crate::TokenKind::ConstIdent, crate::TokenKind::TypeIdent]) {
+                    p.eat(HighlightGroup::ConstantDef);
+                } else {
+                    p.push(crate::TokenKind::ConstIdent, HighlightGroup::ConstantDef);
+                }
+                p.push(crate::TokenKind::Colon, HighlightGroup::Separator);
+                parse_ty(p);
+            }
+            // The parameters were never closed, so we leave the item that follows for the caller.
+            _ if p.at(crate::grammar::ITEM_START) => break,
+            _ => p.eat(HighlightGroup::Error),
+        }
+    }
+}
+
+/// Parses a lifetime, highlighting it as a definition if `is_def` is set. `'static` and `'_` are
+/// built into the language and so are always uses; they can’t be declared as parameters.
+fn parse_lifetime(p: &mut Parser<'_>, is_def: bool) {
+    assert!(p.at(&[crate::TokenKind::TickIdent]));
+
+    let is_builtin = p.peek().map_or(false, |token| {
+        matches!(&p.input[token.range.clone()], "'static" | "'_")
+    });
+
+    p.eat(match (is_def, is_builtin) {
+        (true, true) => HighlightGroup::Error,
+        (true, false) => HighlightGroup::SpecialIdentDef,
+        (false, _) => HighlightGroup::SpecialIdentUse,
+    });
+}
+
+// Parses bounds on a generic parameter, as in `Clone + ?Sized + 'a`.
+fn parse_bounds(p: &mut Parser<'_>) {
+    loop {
+        if p.at(&[crate::TokenKind::TickIdent]) {
+            parse_lifetime(p, false);
+        } else {
+            if p.at(&[crate::TokenKind::Question]) {
+                p.eat(HighlightGroup::OtherOper);
+            }
+
+            parse_ty(p);
+        }
+
+        if !p.at(&[crate::TokenKind::Plus]) {
+            break;
+        }
+        p.eat(HighlightGroup::OtherOper);
+    }
+}
+
+fn at_generics_end(p: &mut Parser<'_>) -> bool {
+    // The closing `>` may have been lexed as part of a longer operator when generic arguments
+    // are nested, as in `Vec<Vec<T>>`, so we take it off the front of that operator.
+    let rest = match p.peek().map(|token| &token.kind) {
+        Some(crate::TokenKind::Shr) => Some(crate::TokenKind::Greater),
+        Some(crate::TokenKind::GreaterEquals) => Some(crate::TokenKind::Equals),
+        Some(crate::TokenKind::ShrEquals) => Some(crate::TokenKind::GreaterEquals),
+        _ => None,
+    };
+    if let Some(rest) = rest {
+        p.split_first(crate::TokenKind::Greater, rest);
+    }
+
+    p.at(&[crate::TokenKind::Greater])
+}
+
+fn parse_tuple_ty(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::OpenParen]));
+    p.eat(HighlightGroup::Delimiter);
+
+    loop {
+        if p.at_end() {
+            break;
+        }
+
+        if p.at(&[crate::TokenKind::Comma]) {
+            p.eat(HighlightGroup::Separator);
+        }
+
+        if p.at(&[crate::TokenKind::CloseParen]) {
+            p.eat(HighlightGroup::Delimiter);
+            break;
+        }
+
+        parse_ty(p);
+    }
+}
+
+// Parses both slice types (`[T]`) and array types (`[T; N]`).
+fn parse_array_ty(p: &mut Parser<'_>) {
+    assert!(p.at(&[crate::TokenKind::OpenBracket]));
+    p.eat(HighlightGroup::Delimiter);
+
+    parse_ty(p);
+
+    if p.at(&[crate::TokenKind::Semi]) {
+        p.eat(HighlightGroup::Separator);
+        parse_expr(p, false);
+    }
+
+    p.push(crate::TokenKind::CloseBracket, HighlightGroup::Delimiter);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialect::HighlightedSpan;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_type_name() {
+        let mut parser = Parser::new("String");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![HighlightedSpan {
+                range: 0..6,
+                group: HighlightGroup::TyUse,
+            }],
+        );
+    }
+
+    #[test]
+    fn parses_tuple_type() {
+        let mut parser = Parser::new("(A, Self)");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..2,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 2..3,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 4..8,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_nested_generic_args_closed_by_shr() {
+        let mut parser = Parser::new("Vec<Vec<u8>>");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..3,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 4..7,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 8..10,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_reference_and_pointer_types() {
+        let mut parser = Parser::new("&mut *const [u8; 4]");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 1..4,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 6..11,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 13..15,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 17..18,
+                    group: HighlightGroup::Number,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_lifetimes_in_reference_and_generic_args() {
+        let mut parser = Parser::new("&'a Cow<'static, str>");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 4..7,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 8..15,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 17..20,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_generic_params() {
+        let mut parser = Parser::new("<'a, 'b: 'a, T: Clone + ?Sized + 'b, const N: usize>");
+        parse_generic_params(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::SpecialIdentDef,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 9..11,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 11..12,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 16..21,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 25..30,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::OtherOper,
+                },
+                HighlightedSpan {
+                    range: 33..35,
+                    group: HighlightGroup::SpecialIdentUse,
+                },
+                HighlightedSpan {
+                    range: 35..36,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 37..42,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 43..44,
+                    group: HighlightGroup::ConstantDef,
+                },
+                HighlightedSpan {
+                    range: 44..45,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 46..51,
+                    group: HighlightGroup::PrimitiveTy,
+                },
+                HighlightedSpan {
+                    range: 51..52,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn builtin_lifetimes_cannot_be_params() {
+        let mut parser = Parser::new("<'static, '_>");
+        parse_generic_params(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..8,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 10..12,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_ffi_style_type_names() {
+        let mut parser = Parser::new("(sockaddr_in, *mut FILE)");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..12,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Separator,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::PointerOper,
+                },
+                HighlightedSpan {
+                    range: 15..18,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 19..23,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 23..24,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn all_caps_names_are_types_in_type_position() {
+        let mut parser = Parser::new("HTTP<IO>");
+        parse_ty(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..4,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..7,
+                    group: HighlightGroup::TyUse,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn all_caps_generic_params_are_types() {
+        let mut parser = Parser::new("<IO>");
+        parse_generic_params(&mut parser);
+
+        assert_eq!(
+            parser.output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 1..3,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+}
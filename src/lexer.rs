@@ -1,38 +1,401 @@
+mod conflict_marker;
+mod error;
+mod escape;
+mod file_start;
+
+use conflict_marker::conflict_marker_at;
+pub(crate) use conflict_marker::may_have_conflict_markers;
+use dialect::HighlightGroup;
+pub(crate) use error::LexError;
+pub(crate) use escape::{scan_escape, Mode};
+pub(crate) use file_start::lex_file_start;
 use logos::Logos;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Lexes all of `s`, also returning why each error token is an error.
+pub(crate) fn lex_with_errors(s: &str) -> (Vec<Token>, Vec<(std::ops::Range<usize>, LexError)>) {
+    let mut lexer = Lexer::new(s);
+    lexer.errors = Some(Vec::new());
+
+    let tokens = lexer.by_ref().collect();
+
+    (tokens, lexer.errors.unwrap())
+}
+
+/// Lexes its input lazily, producing tokens in the order they appear.
+pub(crate) struct Lexer<'a> {
+    s: &'a str,
+    inner: logos::Lexer<'a, TokenKind>,
+    start: usize,
+    // A token can be changed by the one after it (see `push_token`), so tokens are held back here
+    // until the next one has been lexed.
+    pending: VecDeque<Token>,
+    errors: Option<Vec<(std::ops::Range<usize>, LexError)>>,
+}
+
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(s: &'a str) -> Self {
+        let mut file_start = Vec::new();
+        let start = lex_file_start(s, &mut file_start);
+
+        let mut lexer = Self::starting_at(s, start);
+        lexer.pending.extend(file_start);
+
+        lexer
+    }
+
+    /// Lexes `s` from `start`, which must be the start of a token past the start of the file.
+    pub(crate) fn starting_at(s: &'a str, start: usize) -> Self {
+        Self {
+            s,
+            inner: TokenKind::lexer(&s[start..]),
+            start,
+            pending: VecDeque::new(),
+            errors: None,
+        }
+    }
+
+    // Lexes the next token into `pending`, returning false at the end of the input.
+    fn lex_next(&mut self) -> bool {
+        let s = self.s;
+
+        // Conflict markers can only begin at the start of a line, which a regex can’t check. We
+        // only look for them where a token starts, so a line inside a string or comment is never
+        // mistaken for one.
+        if let Some(marker) = conflict_marker_at(s, self.start + self.inner.span().end) {
+            self.inner.bump(marker.len());
 
-pub(crate) fn lex(s: &str) -> Vec<Token> {
-    let mut tokens: Vec<_> = TokenKind::lexer(s)
-        .spanned()
-        .map(|(kind, range)| Token { kind, range })
-        .collect();
+            let c = s[marker.clone()].chars().next().unwrap();
+            self.push_error(marker.clone(), LexError::ConflictMarker(c));
+            self.pending.push_back(Token {
+                kind: TokenKind::ConflictMarker,
+                range: marker,
+            });
 
-    tokens.reverse();
+            return true;
+        }
 
-    tokens
+        let kind = match self.inner.next() {
+            Some(kind) => kind,
+            None => return false,
+        };
+
+        let span = self.inner.span();
+        let mut range = self.start + span.start..self.start + span.end;
+        let mut kind = match kind {
+            TokenKind::Ident => classify_ident(&s[range.clone()]),
+            // See `lifetime`.
+            TokenKind::TickIdent if s[range.clone()].ends_with('\'') => TokenKind::Char,
+            _ => kind,
+        };
+
+        // Callbacks record why they failed. Without a callback, the only way a number can fail to
+        // lex is by having an exponent without digits (as in `1e+`), since that regex is the only
+        // one that can fail partway through; anything else is an error because of the character
+        // it starts with.
+        if kind == TokenKind::Error {
+            let error = self.inner.extras.take().unwrap_or_else(|| {
+                match s[range.clone()].chars().next().unwrap() {
+                    '0'..='9' => LexError::NoExponentDigits,
+                    c => LexError::UnknownStart(c),
+                }
+            });
+            self.push_error(range.clone(), error);
+        }
+
+        // Line comments run up to the newline, but with CRLF line endings the `\r` before it
+        // isn’t part of the comment.
+        if matches!(
+            kind,
+            TokenKind::LineComment
+                | TokenKind::OuterLineDocComment
+                | TokenKind::InnerLineDocComment
+        ) && s[range.clone()].ends_with('\r')
+        {
+            range.end -= 1;
+            let cr = range.end..range.end + 1;
+            push_token(&mut self.pending, Token { kind, range }, s);
+
+            kind = TokenKind::Whitespace;
+            range = cr;
+        }
+
+        push_token(&mut self.pending, Token { kind, range }, s);
+
+        true
+    }
+
+    fn push_error(&mut self, range: std::ops::Range<usize>, error: LexError) {
+        if let Some(errors) = &mut self.errors {
+            errors.push((range, error));
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.len() < 2 && self.lex_next() {}
+        self.pending.pop_front()
+    }
+}
+
+impl fmt::Debug for Lexer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lexer")
+            .field("pending", &self.pending)
+            .finish_non_exhaustive()
+    }
+}
+
+// Naming conventions are all we have to go on to tell types (`PascalCase`), constants
+// (`SCREAMING_SNAKE_CASE`) and everything else apart. A lone capital letter, as in `T`, is
+// conventionally a type. Letters from scripts without case count as lowercase. Only the case of
+// letters matters here, which NFC normalisation never changes (it only composes base letters with
+// caseless combining marks), so identifiers are classified the same whichever form they’re in.
+fn classify_ident(s: &str) -> TokenKind {
+    if s == "_" {
+        return TokenKind::Underscore;
+    }
+
+    let name = s.strip_prefix("r#").unwrap_or(s).trim_start_matches('_');
+
+    if !name.starts_with(char::is_uppercase) {
+        return TokenKind::Ident;
+    }
+
+    let has_lowercase = name.chars().any(char::is_lowercase);
+    let letters = name.chars().filter(|c| c.is_alphabetic()).count();
+
+    if has_lowercase || letters == 1 {
+        TokenKind::TypeIdent
+    } else {
+        TokenKind::ConstIdent
+    }
+}
+
+// Whether a `.` following an integer makes it a float is decided by what comes after the `.`:
+// `1.` is a float, but `1..2` is a range and `1.foo()` is a method call. Meanwhile a float directly
+// after a `.` is really two tuple indices, as in `x.0.1`. An integer followed by a `.` and a digit
+// can only be the second of those, as in `x.0.1.2`, since otherwise it would have been lexed as a
+// float. Neither of these can be expressed with regexes, so we fix them up here as each token is
+// added.
+fn push_token(tokens: &mut VecDeque<Token>, token: Token, s: &str) {
+    let previous = match tokens.back_mut() {
+        Some(previous) if previous.range.end == token.range.start => previous,
+        _ => {
+            tokens.push_back(token);
+            return;
+        }
+    };
+
+    match (&previous.kind, &token.kind) {
+        (TokenKind::Int, TokenKind::Dot)
+            if s[previous.range.clone()]
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b == b'_')
+                && !s[token.range.end..].starts_with(|c: char| {
+                    c == '.' || c == '_' || c.is_ascii_digit() || unicode_ident::is_xid_start(c)
+                }) =>
+        {
+            previous.kind = TokenKind::Float;
+            previous.range.end = token.range.end;
+        }
+        (TokenKind::Dot, TokenKind::Float)
+            if s[token.range.clone()]
+                .bytes()
+                .all(|b| b.is_ascii_digit() || b == b'.') =>
+        {
+            let dot = token.range.start + s[token.range.clone()].find('.').unwrap();
+
+            tokens.push_back(Token {
+                kind: TokenKind::Int,
+                range: token.range.start..dot,
+            });
+            tokens.push_back(Token {
+                kind: TokenKind::Dot,
+                range: dot..dot + 1,
+            });
+            tokens.push_back(Token {
+                kind: TokenKind::Int,
+                range: dot + 1..token.range.end,
+            });
+        }
+        _ => tokens.push_back(token),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct Token {
     pub(crate) kind: TokenKind,
     pub(crate) range: std::ops::Range<usize>,
 }
 
-#[derive(Debug, PartialEq, Logos)]
+#[derive(Debug, Clone, PartialEq, Logos)]
+#[logos(extras = Option<LexError>)]
 pub(crate) enum TokenKind {
+    // These three can only appear at the start of a file, so they’re lexed separately.
+    Shebang,
+    FrontmatterFence,
+    Frontmatter,
+    // Lines starting with one of `<<<<<<<`, `|||||||`, `=======` or `>>>>>>>` are also found
+    // separately, since they have to be at the start of a line.
+    ConflictMarker,
+    #[regex("//[^\n]*")]
+    #[regex("////[^\n]*")]
+    LineComment,
+    #[token("/*", block_comment)]
+    #[token("/**/")]
+    #[token("/***", block_comment)]
+    BlockComment,
+    #[regex("///[^\n]*")]
+    OuterLineDocComment,
+    #[regex("//![^\n]*")]
+    InnerLineDocComment,
+    #[token("/**", block_comment)]
+    OuterBlockDocComment,
+    #[token("/*!", block_comment)]
+    InnerBlockDocComment,
+    #[token("as")]
+    As,
+    #[token("async")]
+    Async,
+    #[token("await")]
+    Await,
+    #[token("break")]
+    Break,
+    #[token("const")]
+    Const,
+    #[token("continue")]
+    Continue,
+    #[token("crate")]
+    Crate,
+    #[token("dyn")]
+    Dyn,
+    #[token("else")]
+    Else,
+    #[token("enum")]
+    Enum,
+    #[token("extern")]
+    Extern,
+    #[token("false")]
+    False,
     #[token("fn")]
     Fn,
+    #[token("for")]
+    For,
+    #[token("if")]
+    If,
+    #[token("impl")]
+    Impl,
+    #[token("in")]
+    In,
+    #[token("let")]
+    Let,
+    #[token("loop")]
+    Loop,
+    #[token("match")]
+    Match,
+    #[token("mod")]
+    Mod,
+    #[token("move")]
+    Move,
+    #[token("mut")]
+    Mut,
+    #[token("pub")]
+    Pub,
+    #[token("ref")]
+    Ref,
+    #[token("return")]
+    Return,
+    #[token("self")]
+    SelfValue,
+    #[token("Self")]
+    SelfType,
+    #[token("static")]
+    Static,
     #[token("struct")]
     Struct,
+    #[token("super")]
+    Super,
     #[token("trait")]
     Trait,
-    #[token("let")]
-    Let,
-    #[regex("_?[A-Z][A-Za-z0-9]*")]
+    #[token("true")]
+    True,
+    #[token("type")]
+    Type,
+    #[token("unsafe")]
+    Unsafe,
+    #[token("use")]
+    Use,
+    #[token("where")]
+    Where,
+    #[token("while")]
+    While,
+    // Keywords that are reserved for future use, and as such can’t be used as identifiers.
+    #[token("abstract")]
+    #[token("become")]
+    #[token("box")]
+    #[token("do")]
+    #[token("final")]
+    #[token("gen")]
+    #[token("macro")]
+    #[token("override")]
+    #[token("priv")]
+    #[token("try")]
+    #[token("typeof")]
+    #[token("unsized")]
+    #[token("virtual")]
+    #[token("yield")]
+    ReservedKeyword,
+    #[token("\"", string)]
+    String,
+    #[regex("r#*\"", raw_string)]
+    RawString,
+    // `'a'` needs a regex of its own as otherwise `'a` would be lexed as a lifetime.
+    #[regex(r"'[^'\\\n\r\t]'")]
+    #[token("'", quoted_char)]
+    Char,
+    #[token("b'", quoted_char)]
+    Byte,
+    #[token("b\"", string)]
+    ByteString,
+    #[regex("br#*\"", raw_string)]
+    RawByteString,
+    #[token("c\"", string)]
+    CString,
+    #[regex("cr#*\"", raw_string)]
+    RawCString,
+    #[regex("[0-9][0-9_]*", |lexer| int(lexer, 10))]
+    #[regex("0x[0-9a-fA-F_]*", |lexer| int(lexer, 16))]
+    #[regex("0o[0-9_]*", |lexer| int(lexer, 8))]
+    #[regex("0b[0-9_]*", |lexer| int(lexer, 2))]
+    Int,
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*([eE][+-]?_*[0-9][0-9_]*)?", float)]
+    #[regex(r"[0-9][0-9_]*[eE][+-]?_*[0-9][0-9_]*", float)]
+    Float,
+    // All identifiers are lexed as `Ident`; the lexer then classifies them by case as types, values
+    // or constants.
     TypeIdent,
-    #[regex("_?[a-z][a-z0-9_]*")]
+    #[regex("[A-Za-z_][A-Za-z0-9_]*", ident)]
+    #[regex(r"[^\x00-\x7F]", ident)]
+    #[regex("r#[A-Za-z_][A-Za-z0-9_]*", raw_ident)]
+    #[regex(r"r#[^\x00-\x7F]", raw_ident)]
     Ident,
-    #[regex("'_?[a-z][a-z0-9_]*")]
+    ConstIdent,
+    // A metavariable in a macro, such as `$x`. Any identifier or keyword can follow the `$`, so
+    // this also covers `$crate`.
+    #[regex(r"\$[A-Za-z_][A-Za-z0-9_]*", ident)]
+    MetaVar,
+    // A lifetime or loop label, such as `'a` or `'outer`.
+    #[regex("'[A-Za-z_][A-Za-z0-9_]*", lifetime)]
+    #[regex(r"'[^\x00-\x7F]", lifetime)]
     TickIdent,
+    // Lexed as an identifier, since a leading `_` can be followed by any XID_Continue character
+    // (as in `_é`); only a lone `_` is classified as this.
+    Underscore,
     #[token("(")]
     OpenParen,
     #[token(")")]
@@ -41,202 +404,1618 @@ pub(crate) enum TokenKind {
     OpenBrace,
     #[token("}")]
     CloseBrace,
+    #[token("[")]
+    OpenBracket,
+    #[token("]")]
+    CloseBracket,
+    #[token("+")]
+    Plus,
+    #[token("-")]
+    Minus,
+    #[token("*")]
+    Star,
+    #[token("/")]
+    Slash,
+    #[token("%")]
+    Percent,
+    #[token("^")]
+    Caret,
+    #[token("!")]
+    Bang,
+    #[token("&")]
+    And,
+    #[token("|")]
+    Pipe,
+    #[token("&&")]
+    AndAnd,
+    #[token("||")]
+    PipePipe,
+    // `>>` (and `>=` and `>>=`) also close nested generic arguments, as in `Vec<Vec<T>>`; the
+    // grammar splits these where needed.
+    #[token("<<")]
+    Shl,
+    #[token(">>")]
+    Shr,
     #[token("=")]
     Equals,
+    #[token("+=")]
+    PlusEquals,
+    #[token("-=")]
+    MinusEquals,
+    #[token("*=")]
+    StarEquals,
+    #[token("/=")]
+    SlashEquals,
+    #[token("%=")]
+    PercentEquals,
+    #[token("^=")]
+    CaretEquals,
+    #[token("&=")]
+    AndEquals,
+    #[token("|=")]
+    PipeEquals,
+    #[token("<<=")]
+    ShlEquals,
+    #[token(">>=")]
+    ShrEquals,
+    #[token("==")]
+    DoubleEquals,
+    #[token("!=")]
+    BangEquals,
+    #[token("<")]
+    Less,
+    #[token(">")]
+    Greater,
+    #[token("<=")]
+    LessEquals,
+    #[token(">=")]
+    GreaterEquals,
+    #[token("@")]
+    At,
     #[token(";")]
     Semi,
     #[token(",")]
     Comma,
+    #[token(":")]
+    Colon,
+    #[token(".")]
+    Dot,
+    #[token("..")]
+    DotDot,
+    #[token("...")]
+    DotDotDot,
+    #[token("..=")]
+    DotDotEquals,
     #[token("::")]
     DoubleColon,
     #[token("->")]
     ThinArrow,
+    #[token("=>")]
+    FatArrow,
+    #[token("#")]
+    Pound,
+    #[token("$")]
+    Dollar,
+    #[token("?")]
+    Question,
+    #[token("~")]
+    Tilde,
+    // This is rustc’s definition of whitespace: Unicode’s Pattern_White_Space. A byte order mark
+    // at the start of the file is lexed as whitespace too.
+    #[regex("[ \t\n\r\x0B\x0C]+")]
+    #[token("\u{85}")]
+    #[token("\u{200E}")]
+    #[token("\u{200F}")]
+    #[token("\u{2028}")]
+    #[token("\u{2029}")]
+    Whitespace,
     #[error]
-    #[regex("[ \t\n\r]", logos::skip)]
     Error,
 }
 
+impl TokenKind {
+    /// The group a keyword is highlighted with, or `None` if this isn’t a keyword.
+    pub(crate) fn keyword_group(&self) -> Option<HighlightGroup> {
+        match self {
+            Self::Await
+            | Self::Break
+            | Self::Continue
+            | Self::Else
+            | Self::For
+            | Self::If
+            | Self::In
+            | Self::Loop
+            | Self::Match
+            | Self::Return
+            | Self::While => Some(HighlightGroup::CtrlFlowKeyword),
+
+            Self::True | Self::False => Some(HighlightGroup::Boolean),
+
+            Self::As
+            | Self::Async
+            | Self::Const
+            | Self::Crate
+            | Self::Dyn
+            | Self::Enum
+            | Self::Extern
+            | Self::Fn
+            | Self::Impl
+            | Self::Let
+            | Self::Mod
+            | Self::Move
+            | Self::Mut
+            | Self::Pub
+            | Self::Ref
+            | Self::SelfValue
+            | Self::SelfType
+            | Self::Static
+            | Self::Struct
+            | Self::Super
+            | Self::Trait
+            | Self::Type
+            | Self::Unsafe
+            | Self::Use
+            | Self::Where => Some(HighlightGroup::OtherKeyword),
+
+            _ => None,
+        }
+    }
+
+    /// Trivia can appear between any two tokens, and as such is skipped over by the parser.
+    pub(crate) fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace
+                | Self::ConflictMarker
+                | Self::Shebang
+                | Self::FrontmatterFence
+                | Self::Frontmatter
+                | Self::LineComment
+                | Self::BlockComment
+                | Self::OuterLineDocComment
+                | Self::InnerLineDocComment
+                | Self::OuterBlockDocComment
+                | Self::InnerBlockDocComment
+        )
+    }
+}
+
+// Block comments nest in Rust, which a regular expression cannot express, so we keep track of how
+// deep we are by hand. An unterminated block comment runs to the end of the input.
+//
+// Scanning starts right after the opening `/*` rather than after the whole token, since the token
+// that distinguishes doc comments (`/**`, `/***`) can already include the `*` of the closing `*/`.
+fn block_comment(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let start = lexer.span().start + 2;
+    let rest = &lexer.source().as_bytes()[start..];
+    let mut depth = 1;
+    let mut idx = 0;
+
+    while idx < rest.len() {
+        match &rest[idx..] {
+            [b'/', b'*', ..] => {
+                depth += 1;
+                idx += 2;
+            }
+            [b'*', b'/', ..] => {
+                depth -= 1;
+                idx += 2;
+
+                if depth == 0 {
+                    lexer.bump(start + idx - lexer.span().end);
+                    return true;
+                }
+            }
+            _ => idx += 1,
+        }
+    }
+
+    lexer.bump(start + idx - lexer.span().end);
+    lexer.extras = Some(LexError::UnterminatedBlockComment);
+    false
+}
+
+// Called after the opening quote. Escapes are only skipped over here (so that `\"` doesn’t end the
+// string); they’re checked for validity when the string is highlighted.
+fn string(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let remainder = lexer.remainder().as_bytes();
+    let mut idx = 0;
+
+    while idx < remainder.len() {
+        match remainder[idx] {
+            b'"' => {
+                lexer.bump(idx + 1);
+                return true;
+            }
+            b'\\' => idx += 2,
+            _ => idx += 1,
+        }
+    }
+
+    lexer.bump(remainder.len());
+    lexer.extras = Some(LexError::UnterminatedString);
+    false
+}
+
+// Called after the opening `r#…"` (or `br#…"`, `cr#…"`). The string only ends at a quote followed
+// by as many hashes as it was opened with.
+fn raw_string(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let hashes = lexer.slice().bytes().filter(|b| *b == b'#').count();
+    let remainder = lexer.remainder();
+    let mut idx = 0;
+
+    while let Some(quote_idx) = remainder[idx..].find('"') {
+        let after_quote = idx + quote_idx + 1;
+
+        if remainder[after_quote..]
+            .bytes()
+            .take(hashes)
+            .take_while(|b| *b == b'#')
+            .count()
+            == hashes
+        {
+            lexer.bump(after_quote + hashes);
+            return true;
+        }
+
+        idx = after_quote;
+    }
+
+    lexer.bump(remainder.len());
+    lexer.extras = Some(LexError::UnterminatedRawString);
+    false
+}
+
+// The regexes only match identifiers made up of ASCII characters, or the first character of one
+// that starts with a non-ASCII character; the rest is matched here following the XID rules.
+fn ident(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let first = lexer
+        .slice()
+        .trim_start_matches(['$', '\''])
+        .trim_start_matches("r#")
+        .chars()
+        .next()
+        .unwrap();
+
+    if !(first == '_' || unicode_ident::is_xid_start(first)) {
+        return false;
+    }
+
+    let len = lexer
+        .remainder()
+        .chars()
+        .take_while(|c| unicode_ident::is_xid_continue(*c))
+        .map(char::len_utf8)
+        .sum();
+    lexer.bump(len);
+
+    true
+}
+
+// A quote right after the name means this was a char literal rather than a lifetime. If it has a
+// single character, as in `'é'`, the token is turned into a `Char` afterwards -- the `Char` regex
+// only wins over this one by itself when that character is ASCII. Otherwise it has too many
+// characters, as in `'ab'`, so we include the closing quote and mark the whole thing as an error.
+fn lifetime(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    if !ident(lexer) {
+        return false;
+    }
+
+    if lexer.remainder().starts_with('\'') {
+        let is_char = lexer.slice()[1..].chars().count() == 1;
+        lexer.bump(1);
+
+        if !is_char {
+            lexer.extras = Some(LexError::CharTooLong);
+            return false;
+        }
+    }
+
+    true
+}
+
+// Any keyword can be used as a raw identifier, apart from those that refer to a path segment and
+// `_`, which isn’t an identifier at all.
+fn raw_ident(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    if !ident(lexer) {
+        return false;
+    }
+
+    let ident = &lexer.slice()[2..];
+    if matches!(ident, "crate" | "self" | "super" | "Self" | "_") {
+        lexer.extras = Some(LexError::InvalidRawIdent(ident.to_string()));
+        return false;
+    }
+
+    true
+}
+
+// Called after the opening quote of a literal containing exactly one character or escape. If the
+// literal is malformed we skip to the closing quote if there is one on the same line, so that the
+// whole literal is marked as an error rather than leaving its remains to confuse the parser.
+fn quoted_char(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let remainder = lexer.remainder();
+    let first = remainder.chars().next();
+
+    let len = match first {
+        Some('\\') => scan_escape(remainder, Mode::Char).len,
+        Some('\'') | Some('\n') | Some('\r') | Some('\t') | None => 0,
+        Some(c) => c.len_utf8(),
+    };
+
+    if len > 0 && remainder[len..].starts_with('\'') {
+        lexer.bump(len + 1);
+        return true;
+    }
+
+    let line = remainder.split('\n').next().unwrap();
+    let closing_quote = line.find('\'');
+    lexer.bump(closing_quote.map_or(len, |idx| idx + 1));
+
+    lexer.extras = Some(match (first, closing_quote) {
+        (Some('\''), _) => LexError::EmptyChar,
+        (Some(c @ '\r'), Some(_)) | (Some(c @ '\t'), Some(_)) => LexError::UnescapedChar(c),
+        (_, Some(_)) => LexError::CharTooLong,
+        (_, None) => LexError::UnterminatedChar,
+    });
+    false
+}
+
+// The regexes for integers accept digits that are invalid for the base (e.g. `0b102`) so that the
+// whole literal is lexed as one token; here we check the digits, as well as lexing and checking the
+// suffix.
+fn int(lexer: &mut logos::Lexer<'_, TokenKind>, radix: u32) -> bool {
+    let suffix_len = lexer
+        .remainder()
+        .bytes()
+        .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        .count();
+    lexer.bump(suffix_len);
+
+    let slice = lexer.slice();
+    let (digits, suffix) = slice.split_at(slice.len() - suffix_len);
+    let digits = if radix == 10 { digits } else { &digits[2..] };
+
+    let error = if !digits.chars().all(|c| c == '_' || c.is_digit(radix)) {
+        Some(LexError::InvalidDigit { radix })
+    } else if !digits.chars().any(|c| c != '_') {
+        Some(LexError::NoDigits)
+    } else {
+        match suffix {
+            "" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+            | "i128" | "isize" => None,
+            // An integer with a float suffix is a float, which is only allowed in decimal.
+            "f32" | "f64" if radix == 10 => None,
+            // The float regexes only match exponents with digits, so `1e` ends up here.
+            "e" | "E" if radix == 10 => Some(LexError::NoExponentDigits),
+            _ => Some(LexError::InvalidSuffix(suffix.to_string())),
+        }
+    };
+
+    lexer.extras = error;
+    lexer.extras.is_none()
+}
+
+fn float(lexer: &mut logos::Lexer<'_, TokenKind>) -> bool {
+    let suffix_len = lexer
+        .remainder()
+        .bytes()
+        .take_while(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        .count();
+    lexer.bump(suffix_len);
+
+    let slice = lexer.slice();
+    let suffix = &slice[slice.len() - suffix_len..];
+
+    if matches!(suffix, "" | "f32" | "f64") {
+        return true;
+    }
+
+    lexer.extras = Some(LexError::InvalidSuffix(suffix.to_string()));
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::{assert_eq, assert_ne};
 
+    // Whitespace is kept as a token so that nothing is lost, but most tests don’t care about it.
+    struct Lexer<'a>(logos::Lexer<'a, TokenKind>);
+
+    impl<'a> Lexer<'a> {
+        fn slice(&self) -> &'a str {
+            self.0.slice()
+        }
+    }
+
+    impl Iterator for Lexer<'_> {
+        type Item = TokenKind;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.find(|kind| *kind != TokenKind::Whitespace)
+        }
+    }
+
+    fn lexer(input: &str) -> Lexer<'_> {
+        Lexer(TokenKind::lexer(input))
+    }
+
+    fn check_whitespace(input: &str) {
+        let mut lexer = TokenKind::lexer(input);
+        assert_eq!(lexer.next(), Some(TokenKind::Whitespace));
+        assert_eq!(lexer.slice(), input);
+        assert_eq!(lexer.next(), None);
+    }
+
     #[test]
     fn lexes_nothing() {
-        assert_eq!(TokenKind::lexer("").count(), 0);
+        assert_eq!(lexer("").count(), 0);
+    }
+
+    #[test]
+    fn lexes_spaces_as_whitespace() {
+        check_whitespace("  ");
+    }
+
+    #[test]
+    fn lexes_tabs_as_whitespace() {
+        check_whitespace("\t\t\t");
+    }
+
+    #[test]
+    fn lexes_line_feeds_as_whitespace() {
+        check_whitespace("\n\n\n\n");
+    }
+
+    #[test]
+    fn lexes_carriage_returns_as_whitespace() {
+        check_whitespace("\r\r");
+    }
+
+    #[test]
+    fn lexes_line_comment() {
+        let mut lexer = lexer("// a comment\nfn");
+        assert_eq!(lexer.next(), Some(TokenKind::LineComment));
+        assert_eq!(lexer.slice(), "// a comment");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn lexes_empty_line_comment_at_end_of_input() {
+        let mut lexer = lexer("//");
+        assert_eq!(lexer.next(), Some(TokenKind::LineComment));
+        assert_eq!(lexer.slice(), "//");
+    }
+
+    #[test]
+    fn lexes_block_comment() {
+        let mut lexer = lexer("/* a\ncomment */fn");
+        assert_eq!(lexer.next(), Some(TokenKind::BlockComment));
+        assert_eq!(lexer.slice(), "/* a\ncomment */");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn lexes_nested_block_comment() {
+        let mut lexer = lexer("/* a /* b */ c */ fn");
+        assert_eq!(lexer.next(), Some(TokenKind::BlockComment));
+        assert_eq!(lexer.slice(), "/* a /* b */ c */");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_error() {
+        let mut lexer = lexer("/* a /* b */ fn");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "/* a /* b */ fn");
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn lexes_outer_line_doc_comment() {
+        let mut lexer = lexer("/// Docs.");
+        assert_eq!(lexer.next(), Some(TokenKind::OuterLineDocComment));
+        assert_eq!(lexer.slice(), "/// Docs.");
+    }
+
+    #[test]
+    fn lexes_inner_line_doc_comment() {
+        let mut lexer = lexer("//! Docs.");
+        assert_eq!(lexer.next(), Some(TokenKind::InnerLineDocComment));
+        assert_eq!(lexer.slice(), "//! Docs.");
+    }
+
+    #[test]
+    fn four_slashes_is_not_doc_comment() {
+        let mut lexer = lexer("//// Not docs.");
+        assert_eq!(lexer.next(), Some(TokenKind::LineComment));
+        assert_eq!(lexer.slice(), "//// Not docs.");
+    }
+
+    #[test]
+    fn lexes_outer_block_doc_comment() {
+        let mut lexer = lexer("/** Docs. */");
+        assert_eq!(lexer.next(), Some(TokenKind::OuterBlockDocComment));
+        assert_eq!(lexer.slice(), "/** Docs. */");
+    }
+
+    #[test]
+    fn lexes_inner_block_doc_comment() {
+        let mut lexer = lexer("/*! Docs. /* nested */ */");
+        assert_eq!(lexer.next(), Some(TokenKind::InnerBlockDocComment));
+        assert_eq!(lexer.slice(), "/*! Docs. /* nested */ */");
+    }
+
+    #[test]
+    fn three_stars_is_not_doc_comment() {
+        let mut lexer = lexer("/*** Not docs. */");
+        assert_eq!(lexer.next(), Some(TokenKind::BlockComment));
+        assert_eq!(lexer.slice(), "/*** Not docs. */");
+    }
+
+    #[test]
+    fn three_stars_and_slash_is_not_doc_comment() {
+        let mut lexer = lexer("/***/fn");
+        assert_eq!(lexer.next(), Some(TokenKind::BlockComment));
+        assert_eq!(lexer.slice(), "/***/");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn empty_block_comment_is_not_doc_comment() {
+        let mut lexer = lexer("/**/fn");
+        assert_eq!(lexer.next(), Some(TokenKind::BlockComment));
+        assert_eq!(lexer.slice(), "/**/");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn lexes_string() {
+        let mut lexer = lexer(r#""a string""#);
+        assert_eq!(lexer.next(), Some(TokenKind::String));
+        assert_eq!(lexer.slice(), r#""a string""#);
+    }
+
+    #[test]
+    fn lexes_multi_line_string() {
+        let mut lexer = lexer("\"a\nstring\" fn");
+        assert_eq!(lexer.next(), Some(TokenKind::String));
+        assert_eq!(lexer.slice(), "\"a\nstring\"");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn escaped_quote_does_not_end_string() {
+        let mut lexer = lexer(r#""\"\\" fn"#);
+        assert_eq!(lexer.next(), Some(TokenKind::String));
+        assert_eq!(lexer.slice(), r#""\"\\""#);
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn unterminated_string_is_error() {
+        let mut lexer = lexer(r#""abc\""#);
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), r#""abc\""#);
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn lexes_raw_string() {
+        let mut lexer = lexer(r#"r"C:\dir""#);
+        assert_eq!(lexer.next(), Some(TokenKind::RawString));
+        assert_eq!(lexer.slice(), r#"r"C:\dir""#);
+    }
+
+    #[test]
+    fn lexes_raw_string_with_hashes() {
+        let mut lexer = lexer(r###"r##"{"a": "#"}"## fn"###);
+        assert_eq!(lexer.next(), Some(TokenKind::RawString));
+        assert_eq!(lexer.slice(), r###"r##"{"a": "#"}"##"###);
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn raw_string_ends_at_first_matching_delimiter() {
+        let mut lexer = lexer(r###"r#"a"##"###);
+        assert_eq!(lexer.next(), Some(TokenKind::RawString));
+        assert_eq!(lexer.slice(), r###"r#"a"#"###);
+    }
+
+    #[test]
+    fn unterminated_raw_string_is_error() {
+        let mut lexer = lexer(r###"r##"a"#"###);
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), r###"r##"a"#"###);
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn r_on_its_own_is_ident() {
+        let mut lexer = lexer("r");
+        assert_eq!(lexer.next(), Some(TokenKind::Ident));
+        assert_eq!(lexer.slice(), "r");
+    }
+
+    #[test]
+    fn lexes_char() {
+        let mut lexer = lexer("'a'");
+        assert_eq!(lexer.next(), Some(TokenKind::Char));
+        assert_eq!(lexer.slice(), "'a'");
+    }
+
+    #[test]
+    fn lexes_multi_byte_char() {
+        check_lex("'é'", &[(TokenKind::Char, "'é'")]);
+    }
+
+    #[test]
+    fn lexes_escaped_chars() {
+        let mut lexer = lexer(r"'\'' '\\' '\x7f' '\u{00e9}'");
+        assert_eq!(lexer.next(), Some(TokenKind::Char));
+        assert_eq!(lexer.slice(), r"'\''");
+        assert_eq!(lexer.next(), Some(TokenKind::Char));
+        assert_eq!(lexer.slice(), r"'\\'");
+        assert_eq!(lexer.next(), Some(TokenKind::Char));
+        assert_eq!(lexer.slice(), r"'\x7f'");
+        assert_eq!(lexer.next(), Some(TokenKind::Char));
+        assert_eq!(lexer.slice(), r"'\u{00e9}'");
+    }
+
+    #[test]
+    fn lifetime_is_not_char() {
+        let mut lexer = lexer("'a 'ab");
+        assert_eq!(lexer.next(), Some(TokenKind::TickIdent));
+        assert_eq!(lexer.slice(), "'a");
+        assert_eq!(lexer.next(), Some(TokenKind::TickIdent));
+        assert_eq!(lexer.slice(), "'ab");
+    }
+
+    #[test]
+    fn lifetime_at_end_of_input_is_not_char() {
+        let mut lexer = lexer("'a");
+        assert_eq!(lexer.next(), Some(TokenKind::TickIdent));
+        assert_eq!(lexer.slice(), "'a");
+        assert_eq!(lexer.next(), None);
+    }
+
+    #[test]
+    fn lexes_byte() {
+        let mut lexer = lexer("b'x'");
+        assert_eq!(lexer.next(), Some(TokenKind::Byte));
+        assert_eq!(lexer.slice(), "b'x'");
+    }
+
+    #[test]
+    fn lexes_escaped_byte() {
+        let mut lexer = lexer(r"b'\'' b'\x7f'");
+        assert_eq!(lexer.next(), Some(TokenKind::Byte));
+        assert_eq!(lexer.slice(), r"b'\''");
+        assert_eq!(lexer.next(), Some(TokenKind::Byte));
+        assert_eq!(lexer.slice(), r"b'\x7f'");
     }
 
     #[test]
-    fn skips_spaces() {
-        assert_eq!(TokenKind::lexer("  ").count(), 0);
+    fn byte_with_several_chars_is_error() {
+        let mut lexer = lexer("b'ab' fn");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "b'ab'");
+        assert_eq!(lexer.next(), Some(TokenKind::Fn));
+    }
+
+    #[test]
+    fn empty_byte_is_error() {
+        let mut lexer = lexer("b''");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "b''");
+    }
+
+    #[test]
+    fn lexes_byte_string() {
+        let mut lexer = lexer(r#"b"bytes\"""#);
+        assert_eq!(lexer.next(), Some(TokenKind::ByteString));
+        assert_eq!(lexer.slice(), r#"b"bytes\"""#);
+    }
+
+    #[test]
+    fn lexes_raw_byte_string() {
+        let mut lexer = lexer(r##"br#"a"b"#"##);
+        assert_eq!(lexer.next(), Some(TokenKind::RawByteString));
+        assert_eq!(lexer.slice(), r##"br#"a"b"#"##);
+    }
+
+    #[test]
+    fn lexes_c_string() {
+        let mut lexer = lexer(r#"c"hello""#);
+        assert_eq!(lexer.next(), Some(TokenKind::CString));
+        assert_eq!(lexer.slice(), r#"c"hello""#);
+    }
+
+    #[test]
+    fn lexes_raw_c_string() {
+        let mut lexer = lexer(r##"cr#"a"b"#"##);
+        assert_eq!(lexer.next(), Some(TokenKind::RawCString));
+        assert_eq!(lexer.slice(), r##"cr#"a"b"#"##);
+    }
+
+    #[test]
+    fn lexes_decimal_int() {
+        let mut lexer = lexer("1_000");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "1_000");
     }
 
     #[test]
-    fn skips_tabs() {
-        assert_eq!(TokenKind::lexer("\t\t\t").count(), 0);
+    fn lexes_ints_in_other_bases() {
+        let mut lexer = lexer("0xDEAD_beef 0o777 0b1010");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0xDEAD_beef");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0o777");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0b1010");
     }
 
     #[test]
-    fn skips_line_feeds() {
-        assert_eq!(TokenKind::lexer("\n\n\n\n").count(), 0);
+    fn lexes_ints_with_suffixes() {
+        let mut lexer = lexer("10u8 0xFFi64 1_000usize 0b1_i128");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "10u8");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0xFFi64");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "1_000usize");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0b1_i128");
     }
 
     #[test]
-    fn skips_carriage_returns() {
-        assert_eq!(TokenKind::lexer("\r\r").count(), 0);
+    fn hex_digits_are_not_suffix() {
+        let mut lexer = lexer("0x1f32");
+        assert_eq!(lexer.next(), Some(TokenKind::Int));
+        assert_eq!(lexer.slice(), "0x1f32");
+    }
+
+    #[test]
+    fn int_with_invalid_digit_for_base_is_error() {
+        let mut lexer = lexer("0b102 0o8");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "0b102");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "0o8");
+    }
+
+    #[test]
+    fn int_without_digits_is_error() {
+        let mut lexer = lexer("0x 0b__");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "0x");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "0b__");
+    }
+
+    #[test]
+    fn int_with_invalid_suffix_is_error() {
+        let mut lexer = lexer("1foo 0x1f64_u7");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "1foo");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "0x1f64_u7");
+    }
+
+    #[test]
+    fn lexes_floats() {
+        let mut lexer = lexer("1.5 1e10 2.5E-3f32 1_000.0_1e+1_0f64");
+        assert_eq!(lexer.next(), Some(TokenKind::Float));
+        assert_eq!(lexer.slice(), "1.5");
+        assert_eq!(lexer.next(), Some(TokenKind::Float));
+        assert_eq!(lexer.slice(), "1e10");
+        assert_eq!(lexer.next(), Some(TokenKind::Float));
+        assert_eq!(lexer.slice(), "2.5E-3f32");
+        assert_eq!(lexer.next(), Some(TokenKind::Float));
+        assert_eq!(lexer.slice(), "1_000.0_1e+1_0f64");
+    }
+
+    #[test]
+    fn float_with_int_suffix_is_error() {
+        let mut lexer = lexer("1.5u8");
+        assert_eq!(lexer.next(), Some(TokenKind::Error));
+        assert_eq!(lexer.slice(), "1.5u8");
+    }
+
+    // Whitespace is left out of the tokens being checked, since it would only get in the way.
+    fn check_lex(input: &str, expected: &[(TokenKind, &str)]) {
+        let tokens: Vec<_> = super::Lexer::new(input).collect();
+
+        let tokens: Vec<_> = tokens
+            .iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (&token.kind, &input[token.range.clone()]))
+            .collect();
+        let expected: Vec<_> = expected.iter().map(|(kind, text)| (kind, *text)).collect();
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn int_followed_by_dot_is_float() {
+        check_lex("1. ", &[(TokenKind::Float, "1.")]);
+        check_lex("1.", &[(TokenKind::Float, "1.")]);
+        check_lex(
+            "(1.)",
+            &[
+                (TokenKind::OpenParen, "("),
+                (TokenKind::Float, "1."),
+                (TokenKind::CloseParen, ")"),
+            ],
+        );
+    }
+
+    #[test]
+    fn int_followed_by_two_dots_is_range() {
+        check_lex(
+            "1..2",
+            &[
+                (TokenKind::Int, "1"),
+                (TokenKind::DotDot, ".."),
+                (TokenKind::Int, "2"),
+            ],
+        );
+    }
+
+    #[test]
+    fn int_followed_by_dot_and_ident_is_method_call() {
+        check_lex(
+            "1.foo()",
+            &[
+                (TokenKind::Int, "1"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Ident, "foo"),
+                (TokenKind::OpenParen, "("),
+                (TokenKind::CloseParen, ")"),
+            ],
+        );
+    }
+
+    #[test]
+    fn float_after_dot_is_nested_tuple_field_access() {
+        check_lex(
+            "x.0.1",
+            &[
+                (TokenKind::Ident, "x"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Int, "0"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Int, "1"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_three_levels_of_tuple_field_access() {
+        check_lex(
+            "x.0.1.2;",
+            &[
+                (TokenKind::Ident, "x"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Int, "0"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Int, "1"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Int, "2"),
+                (TokenKind::Semi, ";"),
+            ],
+        );
+    }
+
+    #[test]
+    fn float_after_spaced_dot_is_float() {
+        check_lex(
+            "x. 0.1",
+            &[
+                (TokenKind::Ident, "x"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Float, "0.1"),
+            ],
+        );
     }
 
     #[test]
     fn lexes_fn() {
-        let mut lexer = TokenKind::lexer("fn");
+        let mut lexer = lexer("fn");
         assert_eq!(lexer.next(), Some(TokenKind::Fn));
         assert_eq!(lexer.slice(), "fn");
     }
 
     #[test]
     fn lexes_struct() {
-        let mut lexer = TokenKind::lexer("struct");
+        let mut lexer = lexer("struct");
         assert_eq!(lexer.next(), Some(TokenKind::Struct));
         assert_eq!(lexer.slice(), "struct");
     }
 
     #[test]
     fn lexes_trait() {
-        let mut lexer = TokenKind::lexer("trait");
+        let mut lexer = lexer("trait");
         assert_eq!(lexer.next(), Some(TokenKind::Trait));
         assert_eq!(lexer.slice(), "trait");
     }
 
     #[test]
     fn lexes_let() {
-        let mut lexer = TokenKind::lexer("let");
+        let mut lexer = lexer("let");
         assert_eq!(lexer.next(), Some(TokenKind::Let));
         assert_eq!(lexer.slice(), "let");
     }
 
+    #[test]
+    fn lexes_shebang() {
+        check_lex(
+            "#!/usr/bin/env rust-script\nfn",
+            &[
+                (TokenKind::Shebang, "#!/usr/bin/env rust-script"),
+                (TokenKind::Fn, "fn"),
+            ],
+        );
+    }
+
+    #[test]
+    fn inner_attribute_is_not_shebang() {
+        check_lex(
+            "#![a]",
+            &[
+                (TokenKind::Pound, "#"),
+                (TokenKind::Bang, "!"),
+                (TokenKind::OpenBracket, "["),
+                (TokenKind::Ident, "a"),
+                (TokenKind::CloseBracket, "]"),
+            ],
+        );
+    }
+
+    #[test]
+    fn inner_attribute_after_comment_is_not_shebang() {
+        check_lex(
+            "#! /* a */\n[b]",
+            &[
+                (TokenKind::Pound, "#"),
+                (TokenKind::Bang, "!"),
+                (TokenKind::BlockComment, "/* a */"),
+                (TokenKind::OpenBracket, "["),
+                (TokenKind::Ident, "b"),
+                (TokenKind::CloseBracket, "]"),
+            ],
+        );
+    }
+
+    #[test]
+    fn shebang_is_only_lexed_at_start_of_file() {
+        check_lex(
+            " #!/a",
+            &[
+                (TokenKind::Pound, "#"),
+                (TokenKind::Bang, "!"),
+                (TokenKind::Slash, "/"),
+                (TokenKind::Ident, "a"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_frontmatter_after_shebang() {
+        check_lex(
+            "#!/usr/bin/env cargo\n\n---cargo\n[dependencies]\n---\nfn",
+            &[
+                (TokenKind::Shebang, "#!/usr/bin/env cargo"),
+                (TokenKind::FrontmatterFence, "---cargo"),
+                (TokenKind::Frontmatter, "[dependencies]\n"),
+                (TokenKind::FrontmatterFence, "---"),
+                (TokenKind::Fn, "fn"),
+            ],
+        );
+    }
+
+    #[test]
+    fn frontmatter_closing_fence_must_match_opening_fence() {
+        check_lex(
+            "----\n---\n----",
+            &[
+                (TokenKind::FrontmatterFence, "----"),
+                (TokenKind::Frontmatter, "---\n"),
+                (TokenKind::FrontmatterFence, "----"),
+            ],
+        );
+    }
+
+    #[test]
+    fn unclosed_frontmatter_is_not_frontmatter() {
+        check_lex(
+            "---\nfn",
+            &[
+                (TokenKind::Minus, "-"),
+                (TokenKind::Minus, "-"),
+                (TokenKind::Minus, "-"),
+                (TokenKind::Fn, "fn"),
+            ],
+        );
+    }
+
+    #[test]
+    fn skips_all_whitespace() {
+        check_lex(
+            "a \t\n\r\x0B\x0C\u{85}\u{200E}\u{200F}\u{2028}\u{2029}b",
+            &[(TokenKind::Ident, "a"), (TokenKind::Ident, "b")],
+        );
+    }
+
+    #[test]
+    fn non_pattern_white_space_is_error() {
+        check_lex(
+            "a\u{A0}b",
+            &[
+                (TokenKind::Ident, "a"),
+                (TokenKind::Error, "\u{A0}"),
+                (TokenKind::Ident, "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn skips_byte_order_mark() {
+        check_lex(
+            "\u{FEFF}#!/bin/x\r\nfn",
+            &[(TokenKind::Shebang, "#!/bin/x"), (TokenKind::Fn, "fn")],
+        );
+    }
+
+    #[test]
+    fn line_comments_do_not_include_carriage_return() {
+        check_lex(
+            "// a\r\n/// b\r\n//! c\r\nd",
+            &[
+                (TokenKind::LineComment, "// a"),
+                (TokenKind::OuterLineDocComment, "/// b"),
+                (TokenKind::InnerLineDocComment, "//! c"),
+                (TokenKind::Ident, "d"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_frontmatter_with_crlf() {
+        check_lex(
+            "---cargo\r\na\r\n---\r\n",
+            &[
+                (TokenKind::FrontmatterFence, "---cargo"),
+                (TokenKind::Frontmatter, "a\r\n"),
+                (TokenKind::FrontmatterFence, "---"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_keywords() {
+        let keywords = [
+            ("as", TokenKind::As),
+            ("async", TokenKind::Async),
+            ("await", TokenKind::Await),
+            ("break", TokenKind::Break),
+            ("const", TokenKind::Const),
+            ("continue", TokenKind::Continue),
+            ("crate", TokenKind::Crate),
+            ("dyn", TokenKind::Dyn),
+            ("else", TokenKind::Else),
+            ("enum", TokenKind::Enum),
+            ("extern", TokenKind::Extern),
+            ("false", TokenKind::False),
+            ("for", TokenKind::For),
+            ("if", TokenKind::If),
+            ("impl", TokenKind::Impl),
+            ("in", TokenKind::In),
+            ("loop", TokenKind::Loop),
+            ("match", TokenKind::Match),
+            ("mod", TokenKind::Mod),
+            ("move", TokenKind::Move),
+            ("mut", TokenKind::Mut),
+            ("pub", TokenKind::Pub),
+            ("ref", TokenKind::Ref),
+            ("return", TokenKind::Return),
+            ("self", TokenKind::SelfValue),
+            ("Self", TokenKind::SelfType),
+            ("static", TokenKind::Static),
+            ("super", TokenKind::Super),
+            ("true", TokenKind::True),
+            ("type", TokenKind::Type),
+            ("unsafe", TokenKind::Unsafe),
+            ("use", TokenKind::Use),
+            ("where", TokenKind::Where),
+            ("while", TokenKind::While),
+        ];
+
+        for (keyword, kind) in keywords.iter() {
+            let mut lexer = lexer(keyword);
+            assert_eq!(lexer.next().as_ref(), Some(kind));
+            assert_eq!(lexer.slice(), *keyword);
+        }
+    }
+
+    #[test]
+    fn lexes_reserved_keywords() {
+        let keywords = [
+            "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+            "typeof", "unsized", "virtual", "yield",
+        ];
+
+        for keyword in keywords.iter() {
+            let mut lexer = lexer(keyword);
+            assert_eq!(lexer.next(), Some(TokenKind::ReservedKeyword));
+            assert_eq!(lexer.slice(), *keyword);
+        }
+    }
+
+    #[test]
+    fn contextual_keywords_are_idents() {
+        for keyword in ["union", "auto", "default", "macro_rules", "raw", "safe"].iter() {
+            let mut lexer = lexer(keyword);
+            assert_eq!(lexer.next(), Some(TokenKind::Ident));
+        }
+    }
+
+    #[test]
+    fn idents_starting_with_keywords_are_not_keywords() {
+        check_lex(
+            "iffy Selfish",
+            &[
+                (TokenKind::Ident, "iffy"),
+                (TokenKind::TypeIdent, "Selfish"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_raw_identifiers() {
+        check_lex(
+            "r#type r#Struct r#foo",
+            &[
+                (TokenKind::Ident, "r#type"),
+                (TokenKind::TypeIdent, "r#Struct"),
+                (TokenKind::Ident, "r#foo"),
+            ],
+        );
+    }
+
+    #[test]
+    fn path_keywords_cannot_be_raw_identifiers() {
+        for input in ["r#crate", "r#self", "r#super", "r#Self"].iter() {
+            let mut lexer = lexer(input);
+            assert_eq!(lexer.next(), Some(TokenKind::Error));
+            assert_eq!(lexer.slice(), *input);
+        }
+    }
+
+    #[test]
+    fn raw_strings_are_not_raw_identifiers() {
+        check_lex(r##"r#"a"#"##, &[(TokenKind::RawString, r##"r#"a"#"##)]);
+    }
+
     #[test]
     fn types_are_pascal_case() {
-        let mut lexer = TokenKind::lexer("PascalCase123");
-        assert_eq!(lexer.next(), Some(TokenKind::TypeIdent));
-        assert_eq!(lexer.slice(), "PascalCase123");
+        check_lex("PascalCase123", &[(TokenKind::TypeIdent, "PascalCase123")]);
     }
 
     #[test]
     fn types_cannot_start_with_number() {
-        assert_ne!(
-            TokenKind::lexer("123NotAType").next(),
-            Some(TokenKind::TypeIdent)
-        );
+        assert_ne!(lexer("123NotAType").next(), Some(TokenKind::Ident));
     }
 
     #[test]
     fn types_can_start_with_an_underscore() {
-        let mut lexer = TokenKind::lexer("_Unused123Type");
-        assert_eq!(lexer.next(), Some(TokenKind::TypeIdent));
-        assert_eq!(lexer.slice(), "_Unused123Type");
+        check_lex(
+            "_Unused123Type",
+            &[(TokenKind::TypeIdent, "_Unused123Type")],
+        );
+    }
+
+    #[test]
+    fn single_capital_letter_is_a_type() {
+        check_lex("T", &[(TokenKind::TypeIdent, "T")]);
     }
 
     #[test]
     fn idents_are_snake_case() {
-        let mut lexer = TokenKind::lexer("snake_123_case");
-        assert_eq!(lexer.next(), Some(TokenKind::Ident));
-        assert_eq!(lexer.slice(), "snake_123_case");
+        check_lex("snake_123_case", &[(TokenKind::Ident, "snake_123_case")]);
     }
 
     #[test]
     fn idents_cannot_start_with_number() {
-        assert_ne!(TokenKind::lexer("123ident").next(), Some(TokenKind::Ident));
+        assert_ne!(lexer("123ident").next(), Some(TokenKind::Ident));
     }
 
     #[test]
     fn idents_can_start_with_an_underscore() {
-        let mut lexer = TokenKind::lexer("_unused_ident");
-        assert_eq!(lexer.next(), Some(TokenKind::Ident));
-        assert_eq!(lexer.slice(), "_unused_ident");
+        check_lex("_unused_ident", &[(TokenKind::Ident, "_unused_ident")]);
+    }
+
+    #[test]
+    fn constants_are_screaming_snake_case() {
+        check_lex(
+            "MAX_LEN __FOO X1",
+            &[
+                (TokenKind::ConstIdent, "MAX_LEN"),
+                (TokenKind::ConstIdent, "__FOO"),
+                (TokenKind::TypeIdent, "X1"),
+            ],
+        );
+    }
+
+    #[test]
+    fn idents_not_following_naming_conventions_are_not_split() {
+        check_lex(
+            "fooBar Foo_bar HTTPServer __ r#fooBar",
+            &[
+                (TokenKind::Ident, "fooBar"),
+                (TokenKind::TypeIdent, "Foo_bar"),
+                (TokenKind::TypeIdent, "HTTPServer"),
+                (TokenKind::Ident, "__"),
+                (TokenKind::Ident, "r#fooBar"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_unicode_idents() {
+        check_lex(
+            "größe 计算 Ünïcödé ÄÖÜ r#größe",
+            &[
+                (TokenKind::Ident, "größe"),
+                (TokenKind::Ident, "计算"),
+                (TokenKind::TypeIdent, "Ünïcödé"),
+                (TokenKind::ConstIdent, "ÄÖÜ"),
+                (TokenKind::Ident, "r#größe"),
+            ],
+        );
+    }
+
+    #[test]
+    fn decomposed_idents_are_classified_like_composed_ones() {
+        check_lex("U\u{308}ber", &[(TokenKind::TypeIdent, "U\u{308}ber")]);
+    }
+
+    #[test]
+    fn non_xid_chars_are_errors_on_char_boundaries() {
+        check_lex(
+            "a→b",
+            &[
+                (TokenKind::Ident, "a"),
+                (TokenKind::Error, "→"),
+                (TokenKind::Ident, "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn idents_cannot_start_with_combining_mark() {
+        check_lex(
+            "\u{308}a",
+            &[(TokenKind::Error, "\u{308}"), (TokenKind::Ident, "a")],
+        );
+    }
+
+    #[test]
+    fn int_followed_by_dot_and_unicode_ident_is_method_call() {
+        check_lex(
+            "1.größe",
+            &[
+                (TokenKind::Int, "1"),
+                (TokenKind::Dot, "."),
+                (TokenKind::Ident, "größe"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lone_underscore_is_not_an_ident() {
+        check_lex("_", &[(TokenKind::Underscore, "_")]);
+    }
+
+    #[test]
+    fn underscore_followed_by_non_ascii_is_an_ident() {
+        check_lex(
+            "_é _größe",
+            &[(TokenKind::Ident, "_é"), (TokenKind::Ident, "_größe")],
+        );
     }
 
     #[test]
-    fn lifetimes_are_snake_case_with_quote() {
-        let mut lexer = TokenKind::lexer("'snake_case");
+    fn lifetimes_are_idents_with_quote() {
+        let mut lexer = lexer("'snake_case");
         assert_eq!(lexer.next(), Some(TokenKind::TickIdent));
         assert_eq!(lexer.slice(), "'snake_case");
     }
 
     #[test]
-    fn lifetimes_cannot_start_with_number() {
-        assert_ne!(
-            TokenKind::lexer("'123lifetime").next(),
-            Some(TokenKind::TickIdent)
+    fn lifetimes_can_be_uppercase() {
+        check_lex(
+            "'A 'Static",
+            &[
+                (TokenKind::TickIdent, "'A"),
+                (TokenKind::TickIdent, "'Static"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lifetimes_can_start_with_non_ascii() {
+        check_lex(
+            "'ä 'größe",
+            &[
+                (TokenKind::TickIdent, "'ä"),
+                (TokenKind::TickIdent, "'größe"),
+            ],
         );
     }
 
+    #[test]
+    fn lexes_special_lifetimes() {
+        check_lex(
+            "'static '_",
+            &[
+                (TokenKind::TickIdent, "'static"),
+                (TokenKind::TickIdent, "'_"),
+            ],
+        );
+    }
+
+    #[test]
+    fn char_with_multiple_characters_is_not_lifetime() {
+        check_lex(
+            "'ab' 'c",
+            &[(TokenKind::Error, "'ab'"), (TokenKind::TickIdent, "'c")],
+        );
+    }
+
+    #[test]
+    fn lifetimes_cannot_start_with_number() {
+        assert_ne!(lexer("'123lifetime").next(), Some(TokenKind::TickIdent));
+    }
+
     #[test]
     fn lifetimes_can_start_with_an_underscore() {
-        let mut lexer = TokenKind::lexer("'_unused_lifetime");
+        let mut lexer = lexer("'_unused_lifetime");
         assert_eq!(lexer.next(), Some(TokenKind::TickIdent));
         assert_eq!(lexer.slice(), "'_unused_lifetime");
     }
 
     #[test]
     fn lexes_open_paren() {
-        let mut lexer = TokenKind::lexer("(");
+        let mut lexer = lexer("(");
         assert_eq!(lexer.next(), Some(TokenKind::OpenParen));
         assert_eq!(lexer.slice(), "(");
     }
 
     #[test]
     fn lexes_close_paren() {
-        let mut lexer = TokenKind::lexer(")");
+        let mut lexer = lexer(")");
         assert_eq!(lexer.next(), Some(TokenKind::CloseParen));
         assert_eq!(lexer.slice(), ")");
     }
 
     #[test]
     fn lexes_open_brace() {
-        let mut lexer = TokenKind::lexer("{");
+        let mut lexer = lexer("{");
         assert_eq!(lexer.next(), Some(TokenKind::OpenBrace));
         assert_eq!(lexer.slice(), "{");
     }
 
     #[test]
     fn lexes_close_brace() {
-        let mut lexer = TokenKind::lexer("}");
+        let mut lexer = lexer("}");
         assert_eq!(lexer.next(), Some(TokenKind::CloseBrace));
         assert_eq!(lexer.slice(), "}");
     }
 
     #[test]
     fn lexes_equals() {
-        let mut lexer = TokenKind::lexer("=");
+        let mut lexer = lexer("=");
         assert_eq!(lexer.next(), Some(TokenKind::Equals));
         assert_eq!(lexer.slice(), "=");
     }
 
     #[test]
     fn lexes_semicolon() {
-        let mut lexer = TokenKind::lexer(";");
+        let mut lexer = lexer(";");
         assert_eq!(lexer.next(), Some(TokenKind::Semi));
         assert_eq!(lexer.slice(), ";");
     }
 
     #[test]
     fn lexes_comma() {
-        let mut lexer = TokenKind::lexer(",");
+        let mut lexer = lexer(",");
         assert_eq!(lexer.next(), Some(TokenKind::Comma));
         assert_eq!(lexer.slice(), ",");
     }
 
     #[test]
     fn lexes_double_colon() {
-        let mut lexer = TokenKind::lexer("::");
+        let mut lexer = lexer("::");
         assert_eq!(lexer.next(), Some(TokenKind::DoubleColon));
         assert_eq!(lexer.slice(), "::");
     }
 
+    #[test]
+    fn lexes_punctuation() {
+        let punctuation = [
+            ("[", TokenKind::OpenBracket),
+            ("]", TokenKind::CloseBracket),
+            ("+", TokenKind::Plus),
+            ("-", TokenKind::Minus),
+            ("*", TokenKind::Star),
+            ("/", TokenKind::Slash),
+            ("%", TokenKind::Percent),
+            ("^", TokenKind::Caret),
+            ("!", TokenKind::Bang),
+            ("&", TokenKind::And),
+            ("|", TokenKind::Pipe),
+            ("&&", TokenKind::AndAnd),
+            ("||", TokenKind::PipePipe),
+            ("<<", TokenKind::Shl),
+            (">>", TokenKind::Shr),
+            ("+=", TokenKind::PlusEquals),
+            ("-=", TokenKind::MinusEquals),
+            ("*=", TokenKind::StarEquals),
+            ("/=", TokenKind::SlashEquals),
+            ("%=", TokenKind::PercentEquals),
+            ("^=", TokenKind::CaretEquals),
+            ("&=", TokenKind::AndEquals),
+            ("|=", TokenKind::PipeEquals),
+            ("<<=", TokenKind::ShlEquals),
+            (">>=", TokenKind::ShrEquals),
+            ("==", TokenKind::DoubleEquals),
+            ("!=", TokenKind::BangEquals),
+            ("<", TokenKind::Less),
+            (">", TokenKind::Greater),
+            ("<=", TokenKind::LessEquals),
+            (">=", TokenKind::GreaterEquals),
+            ("@", TokenKind::At),
+            (":", TokenKind::Colon),
+            ("...", TokenKind::DotDotDot),
+            ("..=", TokenKind::DotDotEquals),
+            ("=>", TokenKind::FatArrow),
+            ("#", TokenKind::Pound),
+            ("$", TokenKind::Dollar),
+            ("?", TokenKind::Question),
+            ("~", TokenKind::Tilde),
+        ];
+
+        for (text, kind) in punctuation.iter() {
+            let mut lexer = lexer(text);
+            assert_eq!(lexer.next().as_ref(), Some(kind));
+            assert_eq!(lexer.slice(), *text);
+        }
+    }
+
+    #[test]
+    fn lexes_longest_operator() {
+        check_lex(
+            "a>>=b<-c",
+            &[
+                (TokenKind::Ident, "a"),
+                (TokenKind::ShrEquals, ">>="),
+                (TokenKind::Ident, "b"),
+                (TokenKind::Less, "<"),
+                (TokenKind::Minus, "-"),
+                (TokenKind::Ident, "c"),
+            ],
+        );
+    }
+
+    #[test]
+    fn int_followed_by_inclusive_range_is_not_float() {
+        check_lex(
+            "1..=2",
+            &[
+                (TokenKind::Int, "1"),
+                (TokenKind::DotDotEquals, "..="),
+                (TokenKind::Int, "2"),
+            ],
+        );
+    }
+
     #[test]
     fn lexes_thin_arrow() {
-        let mut lexer = TokenKind::lexer("->");
+        let mut lexer = lexer("->");
         assert_eq!(lexer.next(), Some(TokenKind::ThinArrow));
         assert_eq!(lexer.slice(), "->");
     }
+
+    #[test]
+    fn lexes_conflict_markers() {
+        check_lex(
+            "<<<<<<< HEAD\r\na\n|||||||\nb\n=======\nc\n>>>>>>> main\n",
+            &[
+                (TokenKind::ConflictMarker, "<<<<<<< HEAD"),
+                (TokenKind::Ident, "a"),
+                (TokenKind::ConflictMarker, "|||||||"),
+                (TokenKind::Ident, "b"),
+                (TokenKind::ConflictMarker, "======="),
+                (TokenKind::Ident, "c"),
+                (TokenKind::ConflictMarker, ">>>>>>> main"),
+            ],
+        );
+    }
+
+    #[test]
+    fn conflict_marker_must_start_line() {
+        check_lex(
+            "a <<<<<<< b",
+            &[
+                (TokenKind::Ident, "a"),
+                (TokenKind::Shl, "<<"),
+                (TokenKind::Shl, "<<"),
+                (TokenKind::Shl, "<<"),
+                (TokenKind::Less, "<"),
+                (TokenKind::Ident, "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn marker_lines_in_strings_are_not_conflict_markers() {
+        check_lex(
+            "\"a\n=======\nb\" r#\"\nTitle\n=======\n\"#",
+            &[
+                (TokenKind::String, "\"a\n=======\nb\""),
+                (TokenKind::RawString, "r#\"\nTitle\n=======\n\"#"),
+            ],
+        );
+    }
+
+    #[test]
+    fn marker_lines_in_block_comments_are_not_conflict_markers() {
+        check_lex(
+            "/*\n=======\n*/\na",
+            &[
+                (TokenKind::BlockComment, "/*\n=======\n*/"),
+                (TokenKind::Ident, "a"),
+            ],
+        );
+    }
+
+    #[test]
+    fn unterminated_string_runs_past_conflict_markers() {
+        check_lex(
+            "<<<<<<< a\n\"b\n=======\nc\n>>>>>>> d",
+            &[
+                (TokenKind::ConflictMarker, "<<<<<<< a"),
+                (TokenKind::Error, "\"b\n=======\nc\n>>>>>>> d"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_conflict_marker_after_byte_order_mark() {
+        check_lex(
+            "\u{FEFF}<<<<<<< a\nb",
+            &[
+                (TokenKind::ConflictMarker, "<<<<<<< a"),
+                (TokenKind::Ident, "b"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lexes_metavariables() {
+        check_lex(
+            "$x:expr $crate $ (",
+            &[
+                (TokenKind::MetaVar, "$x"),
+                (TokenKind::Colon, ":"),
+                (TokenKind::Ident, "expr"),
+                (TokenKind::MetaVar, "$crate"),
+                (TokenKind::Dollar, "$"),
+                (TokenKind::OpenParen, "("),
+            ],
+        );
+    }
 }
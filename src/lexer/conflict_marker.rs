@@ -0,0 +1,31 @@
+use std::ops::Range;
+
+const MARKERS: [&str; 4] = ["<<<<<<<", "|||||||", "=======", ">>>>>>>"];
+
+/// Finds a conflict marker left behind by a merge at `start`, which must be the start of a token,
+/// producing its range without the line ending. Like rustc, we only look for the seven characters
+/// of the marker itself at the start of a line; anything after them (such as the name of a branch)
+/// is part of the marker.
+pub(super) fn conflict_marker_at(s: &str, start: usize) -> Option<Range<usize>> {
+    let rest = &s[start..];
+    if !MARKERS.iter().any(|marker| rest.starts_with(marker)) {
+        return None;
+    }
+
+    // A byte order mark isn’t part of the first line, since rustc strips it before lexing.
+    let before = &s[..start];
+    if !(matches!(before, "" | "\u{FEFF}") || before.ends_with('\n')) {
+        return None;
+    }
+
+    let line = rest.split('\n').next().unwrap();
+    let len = line.strip_suffix('\r').unwrap_or(line).len();
+
+    Some(start..start + len)
+}
+
+/// Whether `s` could have any conflict markers in it, which is much quicker to find out than
+/// lexing it.
+pub(crate) fn may_have_conflict_markers(s: &str) -> bool {
+    MARKERS.iter().any(|marker| s.contains(marker))
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+/// Why the lexer produced an error token.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LexError {
+    UnknownStart(char),
+    UnterminatedBlockComment,
+    UnterminatedString,
+    UnterminatedRawString,
+    UnterminatedChar,
+    EmptyChar,
+    UnescapedChar(char),
+    CharTooLong,
+    InvalidDigit { radix: u32 },
+    NoDigits,
+    NoExponentDigits,
+    InvalidSuffix(String),
+    InvalidRawIdent(String),
+    ConflictMarker(char),
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownStart(c) => write!(f, "unknown start of token: {}", c.escape_debug()),
+            Self::UnterminatedBlockComment => write!(f, "unterminated block comment"),
+            Self::UnterminatedString => write!(f, "unterminated string"),
+            Self::UnterminatedRawString => write!(f, "unterminated raw string"),
+            Self::UnterminatedChar => write!(f, "unterminated char literal"),
+            Self::EmptyChar => write!(f, "empty char literal"),
+            Self::UnescapedChar(c) => {
+                write!(
+                    f,
+                    "`{}` must be escaped in a char literal",
+                    c.escape_default()
+                )
+            }
+            Self::CharTooLong => write!(f, "char literal may only contain one character"),
+            Self::InvalidDigit { radix } => write!(f, "invalid digit for base {}", radix),
+            Self::NoDigits => write!(f, "no valid digits found for number"),
+            Self::NoExponentDigits => write!(f, "expected at least one digit in exponent"),
+            Self::InvalidSuffix(suffix) => {
+                write!(f, "invalid suffix `{}` for number literal", suffix)
+            }
+            Self::InvalidRawIdent(ident) => write!(f, "`{}` cannot be a raw identifier", ident),
+            Self::ConflictMarker(c) => {
+                write!(
+                    f,
+                    "encountered git conflict marker `{}`",
+                    c.to_string().repeat(7)
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+/// The kind of literal an escape appears in, which determines the escapes that are allowed.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Mode {
+    Char,
+    Str,
+    Byte,
+    ByteStr,
+    CStr,
+}
+
+impl Mode {
+    fn allows_line_continuation(self) -> bool {
+        match self {
+            Self::Str | Self::ByteStr | Self::CStr => true,
+            Self::Char | Self::Byte => false,
+        }
+    }
+
+    fn allows_unicode_escapes(self) -> bool {
+        match self {
+            Self::Char | Self::Str | Self::CStr => true,
+            Self::Byte | Self::ByteStr => false,
+        }
+    }
+
+    fn max_hex_escape(self) -> u32 {
+        match self {
+            Self::Char | Self::Str => 0x7F,
+            Self::Byte | Self::ByteStr | Self::CStr => 0xFF,
+        }
+    }
+
+    /// Whether a character can appear unescaped in a literal of this kind.
+    pub(crate) fn allows_char(self, c: char) -> bool {
+        match self {
+            Self::Char | Self::Str => true,
+            Self::Byte | Self::ByteStr => c.is_ascii(),
+            Self::CStr => c != '\0',
+        }
+    }
+
+    fn allows_escaped_value(self, value: u32) -> bool {
+        match self {
+            Self::Char | Self::Str | Self::Byte | Self::ByteStr => true,
+            Self::CStr => value != 0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Escape {
+    pub(crate) len: usize,
+    pub(crate) is_valid: bool,
+}
+
+/// Scans the escape at the start of `s`, which must begin with a backslash.
+pub(crate) fn scan_escape(s: &str, mode: Mode) -> Escape {
+    assert!(s.starts_with('\\'));
+    let rest = &s[1..];
+
+    let c = match rest.chars().next() {
+        Some(c) => c,
+        None => {
+            return Escape {
+                len: 1,
+                is_valid: false,
+            }
+        }
+    };
+
+    let after_c = &rest[c.len_utf8()..];
+    let len = 1 + c.len_utf8();
+
+    match c {
+        'n' | 'r' | 't' | '\\' | '\'' | '"' => Escape {
+            len,
+            is_valid: true,
+        },
+        '0' => Escape {
+            len,
+            is_valid: mode.allows_escaped_value(0),
+        },
+        'x' => {
+            let digits = after_c
+                .bytes()
+                .take(2)
+                .take_while(u8::is_ascii_hexdigit)
+                .count();
+
+            let is_valid = digits == 2 && {
+                let value = u32::from_str_radix(&after_c[..2], 16).unwrap();
+                value <= mode.max_hex_escape() && mode.allows_escaped_value(value)
+            };
+
+            Escape {
+                len: len + digits,
+                is_valid,
+            }
+        }
+        'u' => {
+            let escape = scan_unicode_escape(after_c, len, mode);
+
+            Escape {
+                len: escape.len,
+                is_valid: escape.is_valid && mode.allows_unicode_escapes(),
+            }
+        }
+        '\n' | '\r' if mode.allows_line_continuation() => {
+            if c == '\r' && !after_c.starts_with('\n') {
+                return Escape {
+                    len,
+                    is_valid: false,
+                };
+            }
+
+            // A line continuation also swallows all the whitespace at the start of the next line.
+            let whitespace = after_c
+                .bytes()
+                .take_while(|b| matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+                .count();
+
+            Escape {
+                len: len + whitespace,
+                is_valid: true,
+            }
+        }
+        _ => Escape {
+            len,
+            is_valid: false,
+        },
+    }
+}
+
+// `s` is the text following `\u`, and `len` is the length of the escape up to that point.
+fn scan_unicode_escape(s: &str, mut len: usize, mode: Mode) -> Escape {
+    if !s.starts_with('{') {
+        return Escape {
+            len,
+            is_valid: false,
+        };
+    }
+
+    let digits_len = s[1..]
+        .bytes()
+        .take_while(|b| b.is_ascii_hexdigit() || *b == b'_')
+        .count();
+    let digits = &s[1..1 + digits_len];
+    let is_closed = s[1 + digits_len..].starts_with('}');
+
+    len += 1 + digits_len;
+    if is_closed {
+        len += 1;
+    }
+
+    let hex: String = digits.chars().filter(|c| *c != '_').collect();
+
+    let is_valid = is_closed && !digits.starts_with('_') && (1..=6).contains(&hex.len()) && {
+        let value = u32::from_str_radix(&hex, 16).unwrap();
+        std::char::from_u32(value).is_some() && mode.allows_escaped_value(value)
+    };
+
+    Escape { len, is_valid }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn check(input: &str, mode: Mode, len: usize, is_valid: bool) {
+        assert_eq!(scan_escape(input, mode), Escape { len, is_valid });
+    }
+
+    #[test]
+    fn scans_quote_escapes() {
+        check("\\n", Mode::Str, 2, true);
+        check("\\0", Mode::Str, 2, true);
+        check("\\\"", Mode::Str, 2, true);
+        check("\\'", Mode::Char, 2, true);
+    }
+
+    #[test]
+    fn scans_hex_escape() {
+        check("\\x7fabc", Mode::Str, 4, true);
+    }
+
+    #[test]
+    fn hex_escape_above_ascii_is_invalid() {
+        check("\\x80", Mode::Str, 4, false);
+    }
+
+    #[test]
+    fn hex_escape_with_one_digit_is_invalid() {
+        check("\\x7", Mode::Str, 3, false);
+    }
+
+    #[test]
+    fn scans_unicode_escape() {
+        check("\\u{1F600}", Mode::Str, 9, true);
+        check("\\u{1_F6_00}", Mode::Str, 11, true);
+    }
+
+    #[test]
+    fn unicode_escape_of_surrogate_is_invalid() {
+        check("\\u{D800}", Mode::Str, 8, false);
+    }
+
+    #[test]
+    fn unicode_escape_with_too_many_digits_is_invalid() {
+        check("\\u{0000000}", Mode::Str, 11, false);
+    }
+
+    #[test]
+    fn unclosed_unicode_escape_is_invalid() {
+        check("\\u{1F", Mode::Str, 5, false);
+    }
+
+    #[test]
+    fn unicode_escape_without_brace_is_invalid() {
+        check("\\u1F", Mode::Str, 2, false);
+    }
+
+    #[test]
+    fn line_continuation_includes_leading_whitespace_of_next_line() {
+        check("\\\n    a", Mode::Str, 6, true);
+        check("\\\r\n\ta", Mode::Str, 4, true);
+    }
+
+    #[test]
+    fn hex_escape_above_ascii_is_valid_in_bytes() {
+        check("\\xff", Mode::Byte, 4, true);
+        check("\\xff", Mode::ByteStr, 4, true);
+        check("\\xff", Mode::CStr, 4, true);
+    }
+
+    #[test]
+    fn unicode_escape_is_invalid_in_bytes() {
+        check("\\u{41}", Mode::Byte, 6, false);
+        check("\\u{41}", Mode::ByteStr, 6, false);
+    }
+
+    #[test]
+    fn line_continuation_is_invalid_in_char() {
+        check("\\\n", Mode::Char, 2, false);
+    }
+
+    #[test]
+    fn line_continuation_is_invalid_in_byte() {
+        check("\\\n", Mode::Byte, 2, false);
+    }
+
+    #[test]
+    fn nul_escapes_are_invalid_in_c_strings() {
+        check("\\0", Mode::CStr, 2, false);
+        check("\\x00", Mode::CStr, 4, false);
+        check("\\u{0}", Mode::CStr, 5, false);
+    }
+
+    #[test]
+    fn unknown_escape_is_invalid() {
+        check("\\q", Mode::Str, 2, false);
+        check("\\é", Mode::Str, 3, false);
+    }
+}
@@ -0,0 +1,121 @@
+use super::{Token, TokenKind};
+use logos::Logos;
+
+/// Lexes the byte order mark, shebang and frontmatter that can only appear at the very start of a
+/// file, returning where regular lexing should start from.
+pub(crate) fn lex_file_start(s: &str, tokens: &mut Vec<Token>) -> usize {
+    let start = if s.starts_with('\u{FEFF}') {
+        '\u{FEFF}'.len_utf8()
+    } else {
+        0
+    };
+    push_whitespace(tokens, 0..start);
+
+    let shebang_len = shebang_len(&s[start..]);
+
+    if shebang_len > 0 {
+        tokens.push(Token {
+            kind: TokenKind::Shebang,
+            range: start..start + shebang_len,
+        });
+    }
+
+    lex_frontmatter(s, start + shebang_len, tokens)
+}
+
+// rustc only treats `#!` as a shebang if it isn’t followed by `[` (ignoring whitespace and
+// comments), so that an inner attribute such as `#![allow(unused)]` on the first line is left
+// alone.
+fn shebang_len(s: &str) -> usize {
+    if !s.starts_with("#!") {
+        return 0;
+    }
+
+    let next = TokenKind::lexer(&s[2..]).find(|kind| {
+        !matches!(
+            kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    });
+
+    if next == Some(TokenKind::OpenBracket) {
+        return 0;
+    }
+
+    let line = &s[..s.find('\n').unwrap_or(s.len())];
+    line.strip_suffix('\r').unwrap_or(line).len()
+}
+
+// Cargo scripts can have a frontmatter block fenced by lines of three or more dashes, which can
+// only be preceded by the shebang and blank lines. The opening fence can be followed by an
+// infostring (as in `---cargo`), and the closing fence must have as many dashes as the opening one.
+// If there is no closing fence this isn’t a frontmatter block, and we leave it to be lexed (into
+// errors) as usual.
+fn lex_frontmatter(s: &str, start: usize, tokens: &mut Vec<Token>) -> usize {
+    let rest = &s[start..];
+    let blank = rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+
+    // The fence has to be at the start of its line.
+    let open_start = match rest[..blank].rfind('\n') {
+        Some(idx) => start + idx + 1,
+        None => start,
+    };
+
+    let dashes = s[open_start..].bytes().take_while(|b| *b == b'-').count();
+    if dashes < 3 {
+        return start;
+    }
+
+    let open_end = line_end(s, open_start);
+    let body_start = (open_end + 1).min(s.len());
+    let mut line_start = body_start;
+
+    while line_start < s.len() {
+        let end = line_end(s, line_start);
+        let line = s[line_start..end].trim_end();
+
+        if line.len() == dashes && line.bytes().all(|b| b == b'-') {
+            let open_fence_end = open_start + s[open_start..open_end].trim_end().len();
+
+            push_whitespace(tokens, start..open_start);
+            tokens.push(Token {
+                kind: TokenKind::FrontmatterFence,
+                range: open_start..open_fence_end,
+            });
+            push_whitespace(tokens, open_fence_end..body_start);
+
+            if line_start > body_start {
+                tokens.push(Token {
+                    kind: TokenKind::Frontmatter,
+                    range: body_start..line_start,
+                });
+            }
+
+            tokens.push(Token {
+                kind: TokenKind::FrontmatterFence,
+                range: line_start..line_start + dashes,
+            });
+
+            return line_start + dashes;
+        }
+
+        line_start = end + 1;
+    }
+
+    start
+}
+
+fn push_whitespace(tokens: &mut Vec<Token>, range: std::ops::Range<usize>) {
+    if !range.is_empty() {
+        tokens.push(Token {
+            kind: TokenKind::Whitespace,
+            range,
+        });
+    }
+}
+
+// The index of the newline ending the line that starts at `start`, or the end of `s` if there is
+// none.
+fn line_end(s: &str, start: usize) -> usize {
+    s[start..].find('\n').map_or(s.len(), |idx| start + idx)
+}
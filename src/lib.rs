@@ -2,11 +2,13 @@
 
 #![warn(missing_debug_implementations, rust_2018_idioms)]
 
+pub mod diagnostic;
 mod grammar;
 mod lexer;
 mod parser;
+pub mod token;
 
-use lexer::{lex, Token, TokenKind};
+use lexer::{Lexer, Token, TokenKind};
 use parser::Parser;
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
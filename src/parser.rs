@@ -1,38 +1,114 @@
 use dialect::{HighlightGroup, HighlightedSpan};
+use std::collections::VecDeque;
+
+// `nth` can look this many tokens ahead (not counting trivia).
+const LOOKAHEAD: usize = 2;
 
 #[derive(Debug)]
-pub(crate) struct Parser {
-    pub(crate) tokens: Vec<crate::Token>,
+pub(crate) struct Parser<'a> {
+    pub(crate) input: &'a str,
+    lexer: crate::Lexer<'a>,
+    // Tokens are pulled from the lexer as they’re needed, so that there are always at least
+    // `LOOKAHEAD` tokens other than trivia here (unless the input has run out).
+    lookahead: VecDeque<crate::Token>,
+    non_trivia: usize,
+    // When parsing one side of a conflict, tokens on the other sides are left out.
+    sides: Vec<(usize, Option<usize>)>,
+    side: usize,
     pub(crate) output: Vec<HighlightedSpan>,
 }
 
-impl Parser {
-    pub(crate) fn new(s: &str) -> Self {
-        let tokens = crate::lex(s);
-        let output = Vec::with_capacity(tokens.len());
+impl<'a> Parser<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self::on_side(input, vec![(0, None)], 0)
+    }
+
+    fn on_side(input: &'a str, sides: Vec<(usize, Option<usize>)>, side: usize) -> Self {
+        let mut parser = Self {
+            input,
+            lexer: crate::Lexer::new(input),
+            lookahead: VecDeque::new(),
+            non_trivia: 0,
+            sides,
+            side,
+            output: Vec::new(),
+        };
+        parser.fill();
+
+        parser
+    }
+
+    fn fill(&mut self) {
+        while self.non_trivia < LOOKAHEAD {
+            let token = match self.lexer.next() {
+                Some(token) => token,
+                None => return,
+            };
 
-        Self { tokens, output }
+            if side_at(&self.sides, token.range.start).map_or(false, |side| side != self.side) {
+                continue;
+            }
+
+            if !token.kind.is_trivia() {
+                self.non_trivia += 1;
+            }
+            self.lookahead.push_back(token);
+        }
     }
 
     pub(crate) fn next(&mut self) -> Option<crate::Token> {
-        self.tokens.pop()
+        self.eat_trivia();
+        let token = self.lookahead.pop_front()?;
+        self.non_trivia -= 1;
+        self.fill();
+
+        Some(token)
     }
 
     pub(crate) fn peek(&self) -> Option<&crate::Token> {
-        self.tokens.last()
+        self.nth(0)
+    }
+
+    /// Looks `n` tokens ahead, skipping over trivia.
+    pub(crate) fn nth(&self, n: usize) -> Option<&crate::Token> {
+        debug_assert!(n < LOOKAHEAD);
+
+        self.lookahead
+            .iter()
+            .filter(|token| !token.kind.is_trivia())
+            .nth(n)
     }
 
     pub(crate) fn at(&self, kinds: &[crate::TokenKind]) -> bool {
-        self.peek()
+        self.nth_at(0, kinds)
+    }
+
+    pub(crate) fn nth_at(&self, n: usize, kinds: &[crate::TokenKind]) -> bool {
+        self.nth(n)
             .map_or(false, |token| kinds.contains(&token.kind))
     }
 
+    /// Contextual keywords (such as `union`) are lexed as identifiers, since they’re only keywords
+    /// in certain positions; this checks for one `n` tokens ahead.
+    pub(crate) fn nth_at_contextual_kw(&self, n: usize, kw: &str) -> bool {
+        self.nth(n).map_or(false, |token| {
+            token.kind == crate::TokenKind::Ident && &self.input[token.range.clone()] == kw
+        })
+    }
+
     pub(crate) fn at_end(&self) -> bool {
         self.peek().is_none()
     }
 
     pub(crate) fn eat(&mut self, group: HighlightGroup) {
         if let Some(token) = self.next() {
+            // A keyword is still highlighted as such when it turns up somewhere unexpected -- this
+            // also means that keywords the grammar doesn’t handle yet are highlighted correctly.
+            let group = match group {
+                HighlightGroup::Error => token.kind.keyword_group().unwrap_or(group),
+                _ => group,
+            };
+
             self.output.push(HighlightedSpan {
                 range: token.range,
                 group,
@@ -48,19 +124,595 @@ impl Parser {
                 range: token.range,
                 group,
             });
-        } else {
+        } else if !self.at(crate::grammar::ITEM_START) {
+            // We leave the start of an item alone so that whoever is parsing items can recover
+            // from the error by parsing that item, instead of it being lost as an error here.
             self.eat(HighlightGroup::Error);
         }
     }
 
-    pub(crate) fn parse(mut self) -> Vec<HighlightedSpan> {
-        while let Some(token) = self.peek() {
-            match token.kind {
-                crate::TokenKind::Fn => crate::grammar::parse_item(&mut self),
-                _ => self.eat(HighlightGroup::Error),
+    /// Splits the next token after its first character into tokens of the kinds `first` and
+    /// `rest`. The lexer always produces the longest operator it can, so this is how `>>` becomes
+    /// two `>`s when it closes nested generic arguments.
+    pub(crate) fn split_first(&mut self, first: crate::TokenKind, rest: crate::TokenKind) {
+        self.eat_trivia();
+
+        if let Some(token) = self.lookahead.pop_front() {
+            let mid = token.range.start + 1;
+            self.non_trivia += 1;
+
+            self.lookahead.push_front(crate::Token {
+                kind: rest,
+                range: mid..token.range.end,
+            });
+            self.lookahead.push_front(crate::Token {
+                kind: first,
+                range: token.range.start..mid,
+            });
+        }
+    }
+
+    fn eat_trivia(&mut self) {
+        while let Some(token) = self.lookahead.front() {
+            let group = match token.kind {
+                crate::TokenKind::Whitespace => {
+                    self.lookahead.pop_front();
+                    continue;
+                }
+                crate::TokenKind::Shebang | crate::TokenKind::FrontmatterFence => {
+                    HighlightGroup::PreProc
+                }
+                crate::TokenKind::Frontmatter => HighlightGroup::Comment,
+                crate::TokenKind::ConflictMarker => HighlightGroup::Error,
+                crate::TokenKind::LineComment | crate::TokenKind::BlockComment => {
+                    HighlightGroup::Comment
+                }
+                crate::TokenKind::OuterLineDocComment
+                | crate::TokenKind::InnerLineDocComment
+                | crate::TokenKind::OuterBlockDocComment
+                | crate::TokenKind::InnerBlockDocComment => HighlightGroup::DocComment,
+                _ => break,
+            };
+
+            let token = self.lookahead.pop_front().unwrap();
+            self.output.push(HighlightedSpan {
+                range: token.range,
+                group,
+            });
+        }
+    }
+
+    /// Parses the whole input. If it has conflict markers left behind by a merge, each side of
+    /// the conflicts is parsed separately along with the code around them, so that code which only
+    /// makes sense on one side doesn’t throw off how the other is highlighted.
+    pub(crate) fn parse(self) -> Vec<HighlightedSpan> {
+        let sides = conflict_sides(self.input);
+        let side_count = sides.iter().filter_map(|(_, side)| *side).max();
+
+        let side_count = match side_count {
+            Some(side) => side + 1,
+            None => return self.parse_items(),
+        };
+
+        let outputs: Vec<_> = (0..side_count)
+            .map(|side| Parser::on_side(self.input, sides.clone(), side).parse_items())
+            .collect();
+
+        let mut output = Vec::new();
+
+        // Each side is taken from the parse that includes it. The code around the conflicts is in
+        // every parse, so we take it from whichever one makes the fewest errors of it -- a side
+        // with (say) an unclosed delimiter would otherwise ruin everything after the conflict.
+        for (idx, (start, side)) in sides.iter().enumerate() {
+            let end = sides.get(idx + 1).map_or(self.input.len(), |(end, _)| *end);
+            let spans = match side {
+                Some(side) => spans_in(&outputs[*side], *start..end),
+                None => outputs
+                    .iter()
+                    .map(|output| spans_in(output, *start..end))
+                    .min_by_key(|spans| {
+                        spans
+                            .iter()
+                            .filter(|span| span.group == HighlightGroup::Error)
+                            .count()
+                    })
+                    .unwrap(),
+            };
+
+            output.extend_from_slice(spans);
+        }
+
+        output
+    }
+
+    fn parse_items(mut self) -> Vec<HighlightedSpan> {
+        while !self.at_end() {
+            // parse_item stops at a close brace (the end of the enclosing item), but at the top
+            // level there is no enclosing item, so the brace is an error.
+            if self.at(&[crate::TokenKind::CloseBrace]) {
+                self.eat(HighlightGroup::Error);
+            } else {
+                crate::grammar::parse_item(&mut self);
             }
         }
 
+        self.eat_trivia();
+
         self.output
     }
 }
+
+// Finds where each side of each conflict starts and ends, as a list of positions paired with the
+// side the input is on from there onwards. The sides are numbered in order, so with `diff3`-style
+// conflicts the common ancestor is side 1 and the other branch is side 2.
+fn conflict_sides(input: &str) -> Vec<(usize, Option<usize>)> {
+    let mut sides = vec![(0, None)];
+    let mut side = None;
+
+    // Conflicts are rare, so we don’t lex the input an extra time to look for them unless it might
+    // have some.
+    if !crate::lexer::may_have_conflict_markers(input) {
+        return sides;
+    }
+
+    let markers = crate::Lexer::new(input)
+        .filter(|token| token.kind == crate::TokenKind::ConflictMarker)
+        .map(|token| token.range);
+
+    for marker in markers {
+        side = match input.as_bytes()[marker.start] {
+            b'<' => Some(0),
+            b'>' => None,
+            _ => side.map(|side| side + 1),
+        };
+
+        sides.push((marker.start, None));
+        sides.push((marker.end, side));
+    }
+
+    sides
+}
+
+fn spans_in(spans: &[HighlightedSpan], range: std::ops::Range<usize>) -> &[HighlightedSpan] {
+    let from = spans.partition_point(|span| span.range.start < range.start);
+    let to = spans.partition_point(|span| span.range.start < range.end);
+    &spans[from..to]
+}
+
+fn side_at(sides: &[(usize, Option<usize>)], pos: usize) -> Option<usize> {
+    let idx = sides.partition_point(|(start, _)| *start <= pos);
+    sides[idx - 1].1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_items_of_every_kind_at_top_level() {
+        let output = Parser::new("struct A; trait B {} fn c() {}").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 8..9,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 10..15,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 16..17,
+                    group: HighlightGroup::InterfaceDef,
+                },
+                HighlightedSpan {
+                    range: 18..19,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 21..23,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 28..29,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 29..30,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn recovers_at_next_item_after_incomplete_item() {
+        let output = Parser::new("struct A fn b();").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 9..11,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 13..14,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn recovers_at_next_item_after_unclosed_body() {
+        let output = Parser::new("fn m() { foo( } fn n() {}").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 9..12,
+                    group: HighlightGroup::FunctionCall,
+                },
+                HighlightedSpan {
+                    range: 12..13,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 14..15,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 16..18,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 21..22,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 23..24,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn stray_close_brace_at_top_level_is_error() {
+        let output = Parser::new("} struct A;").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..1,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 2..8,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 9..10,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 10..11,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn highlights_comments_between_tokens() {
+        let output = Parser::new("struct /* a */ A // b\n; // c").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..6,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 7..14,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 15..16,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 17..21,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::Terminator,
+                },
+                HighlightedSpan {
+                    range: 24..28,
+                    group: HighlightGroup::Comment,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn highlights_doc_comments_differently_from_comments() {
+        let output = Parser::new("//! a\n/// b\n// c\nstruct A;").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::DocComment,
+                },
+                HighlightedSpan {
+                    range: 6..11,
+                    group: HighlightGroup::DocComment,
+                },
+                HighlightedSpan {
+                    range: 12..16,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 17..23,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::TyDef,
+                },
+                HighlightedSpan {
+                    range: 25..26,
+                    group: HighlightGroup::Terminator,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn output_ranges_of_unicode_input_are_on_char_boundaries() {
+        let input = "fn 计算() { let größe = 1; → }";
+        let output = Parser::new(input).parse();
+
+        for span in &output {
+            assert!(input.get(span.range.clone()).is_some());
+        }
+        assert!(output.contains(&HighlightedSpan {
+            range: 3..9,
+            group: HighlightGroup::FunctionDef,
+        }));
+        assert!(output.contains(&HighlightedSpan {
+            range: 18..25,
+            group: HighlightGroup::VariableDef,
+        }));
+    }
+
+    #[test]
+    fn highlights_shebang_and_frontmatter() {
+        let output = Parser::new("#!/usr/bin/env cargo\n---\na\n---\n").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..20,
+                    group: HighlightGroup::PreProc,
+                },
+                HighlightedSpan {
+                    range: 21..24,
+                    group: HighlightGroup::PreProc,
+                },
+                HighlightedSpan {
+                    range: 25..27,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 27..30,
+                    group: HighlightGroup::PreProc,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parses_each_side_of_conflict_separately() {
+        // The unclosed call on one side doesn’t stop the brace after the conflict from closing the
+        // function body.
+        let output = Parser::new("fn f() {\n<<<<<<< a\ng(\n=======\nh()\n>>>>>>> b\n}").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..2,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 3..4,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 4..5,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 5..6,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 7..8,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 9..18,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 19..20,
+                    group: HighlightGroup::FunctionCall,
+                },
+                HighlightedSpan {
+                    range: 20..21,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 22..29,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 30..31,
+                    group: HighlightGroup::FunctionCall,
+                },
+                HighlightedSpan {
+                    range: 31..32,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 32..33,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 34..43,
+                    group: HighlightGroup::Error,
+                },
+                HighlightedSpan {
+                    range: 44..45,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn looks_ahead_past_trivia() {
+        let output = Parser::new("const /* a */\n// b\nfn c() {}").parse();
+
+        assert_eq!(
+            output,
+            vec![
+                HighlightedSpan {
+                    range: 0..5,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 6..13,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 14..18,
+                    group: HighlightGroup::Comment,
+                },
+                HighlightedSpan {
+                    range: 19..21,
+                    group: HighlightGroup::OtherKeyword,
+                },
+                HighlightedSpan {
+                    range: 22..23,
+                    group: HighlightGroup::FunctionDef,
+                },
+                HighlightedSpan {
+                    range: 23..24,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 24..25,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 26..27,
+                    group: HighlightGroup::Delimiter,
+                },
+                HighlightedSpan {
+                    range: 27..28,
+                    group: HighlightGroup::Delimiter,
+                },
+            ],
+        );
+    }
+}
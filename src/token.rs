@@ -0,0 +1,314 @@
+//! A lossless view of the tokens in Rust source code, independent of highlighting.
+
+use crate::lexer;
+
+/// A token found by [`tokenize`], along with the byte range it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    /// the kind of token this is
+    pub kind: TokenKind,
+    /// the byte range the token covers
+    pub range: std::ops::Range<usize>,
+}
+
+/// The kind of a token. These are kept coarse so that they stay stable as the lexer changes; the
+/// text of a token can be used to tell, say, different punctuation apart.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// whitespace, including a byte order mark at the start of the input
+    Whitespace,
+    /// a line or block comment that isn’t a doc comment
+    Comment,
+    /// an inner or outer doc comment
+    DocComment,
+    /// a shebang on the first line, as in `#!/usr/bin/env rust-script`
+    Shebang,
+    /// a Cargo script’s frontmatter block, or one of the fences around it
+    Frontmatter,
+    /// a strict or reserved keyword (contextual keywords such as `union` are identifiers)
+    Keyword,
+    /// an identifier, including raw identifiers such as `r#type` and macro metavariables such as
+    /// `$x`
+    Ident,
+    /// a lifetime or label, such as `'a`
+    Lifetime,
+    /// a number, character, byte, string, byte string or C string literal
+    Literal,
+    /// a punctuation token, such as `+=` or `{`
+    Punct,
+    /// something that isn’t a valid token (see [`diagnostics`](crate::diagnostic::diagnostics) for why)
+    Error,
+}
+
+/// Splits `input` into tokens, in the order they appear. Trivia such as whitespace and comments is
+/// included, so that concatenating the text of the tokens gives back `input` exactly.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    lexer::Lexer::new(input).map(Token::from).collect()
+}
+
+/// Updates `tokens`, as returned by [`tokenize`], after the text in `range` has been replaced with
+/// `replacement`, giving `input`. Only the tokens around the edit are lexed again, but the result
+/// is always the same as tokenizing `input` from scratch.
+///
+/// # Panics
+///
+/// Panics if `replacement` isn’t at the start of `range` in `input`.
+pub fn retokenize(
+    tokens: &[Token],
+    range: std::ops::Range<usize>,
+    replacement: &str,
+    input: &str,
+) -> Vec<Token> {
+    let new_end = range.start + replacement.len();
+    assert_eq!(input.get(range.start..new_end), Some(replacement));
+
+    // The shebang and frontmatter decide where regular lexing starts, and a frontmatter block
+    // depends on where its closing fence is, so we start from scratch if they’ve changed at all.
+    let mut file_start = Vec::new();
+    let start = lexer::lex_file_start(input, &mut file_start);
+    let file_start: Vec<_> = file_start.into_iter().map(Token::from).collect();
+
+    if start >= range.start || !tokens.starts_with(&file_start) {
+        return tokenize(input);
+    }
+
+    // No token looks ahead past the end of its line, so everything up to a newline that comes
+    // before the edit stays the same.
+    let before_edit = tokens.partition_point(|token| token.range.end < range.start);
+    let restart = tokens[file_start.len()..before_edit]
+        .iter()
+        .rposition(|token| {
+            token.kind == TokenKind::Whitespace && input[token.range.clone()].contains('\n')
+        })
+        .map(|idx| file_start.len() + idx);
+
+    let restart = match restart {
+        Some(restart) => restart,
+        None => return tokenize(input),
+    };
+
+    let restart_end = tokens[restart].range.end;
+
+    if tokens[file_start.len()..]
+        .iter()
+        .take_while(|token| token.range.start <= restart_end)
+        .any(|token| matches!(token.kind, TokenKind::Shebang | TokenKind::Frontmatter))
+    {
+        return tokenize(input);
+    }
+
+    // Lexing stops once it reaches whitespace past the edit that ends where whitespace ended
+    // before the edit, since from there on the old tokens are the same as what we’d get. The
+    // whitespace has to end after at least one character past the edit, so that whether the next
+    // line starts there (and might begin with a conflict marker) hasn’t changed.
+    let mut relexed = Vec::new();
+    let mut rest = tokens.len();
+
+    for token in lexer::Lexer::starting_at(input, restart_end) {
+        let token = Token::from(token);
+        let end = token.range.end;
+        let is_whitespace = token.kind == TokenKind::Whitespace;
+        relexed.push(token);
+
+        if !is_whitespace || end <= new_end {
+            continue;
+        }
+
+        let old_end = end - new_end + range.end;
+        let idx = tokens.partition_point(|token| token.range.end < old_end);
+
+        if matches!(
+            tokens.get(idx),
+            Some(token) if token.range.end == old_end && token.kind == TokenKind::Whitespace
+        ) {
+            rest = idx + 1;
+            break;
+        }
+    }
+
+    let shift = |token: &Token| Token {
+        kind: token.kind,
+        range: token.range.start - range.end + new_end..token.range.end - range.end + new_end,
+    };
+
+    tokens[..=restart]
+        .iter()
+        .cloned()
+        .chain(relexed)
+        .chain(tokens[rest..].iter().map(shift))
+        .collect()
+}
+
+impl From<lexer::Token> for Token {
+    fn from(token: lexer::Token) -> Self {
+        Self {
+            kind: TokenKind::from(&token.kind),
+            range: token.range,
+        }
+    }
+}
+
+impl From<&lexer::TokenKind> for TokenKind {
+    fn from(kind: &lexer::TokenKind) -> Self {
+        use lexer::TokenKind as Kind;
+
+        if kind.keyword_group().is_some() {
+            return Self::Keyword;
+        }
+
+        match kind {
+            Kind::Whitespace => Self::Whitespace,
+            Kind::LineComment | Kind::BlockComment => Self::Comment,
+            Kind::OuterLineDocComment
+            | Kind::InnerLineDocComment
+            | Kind::OuterBlockDocComment
+            | Kind::InnerBlockDocComment => Self::DocComment,
+            Kind::Shebang => Self::Shebang,
+            Kind::FrontmatterFence | Kind::Frontmatter => Self::Frontmatter,
+            Kind::ReservedKeyword => Self::Keyword,
+            Kind::TypeIdent | Kind::Ident | Kind::ConstIdent | Kind::MetaVar => Self::Ident,
+            Kind::TickIdent => Self::Lifetime,
+            Kind::String
+            | Kind::RawString
+            | Kind::Char
+            | Kind::Byte
+            | Kind::ByteString
+            | Kind::RawByteString
+            | Kind::CString
+            | Kind::RawCString
+            | Kind::Int
+            | Kind::Float => Self::Literal,
+            Kind::Error | Kind::ConflictMarker => Self::Error,
+            _ => Self::Punct,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn check_lossless(input: &str) {
+        let tokens = tokenize(input);
+        let mut end = 0;
+
+        for token in &tokens {
+            assert_eq!(token.range.start, end);
+            end = token.range.end;
+        }
+        assert_eq!(end, input.len());
+
+        let text: String = tokens
+            .iter()
+            .map(|token| &input[token.range.clone()])
+            .collect();
+        assert_eq!(text, input);
+    }
+
+    #[test]
+    fn tokenizes_in_source_order_with_trivia() {
+        let input = "let x = 1; // a";
+
+        let tokens: Vec<_> = tokenize(input)
+            .into_iter()
+            .map(|token| (token.kind, &input[token.range]))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Keyword, "let"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Ident, "x"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Punct, "="),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Literal, "1"),
+                (TokenKind::Punct, ";"),
+                (TokenKind::Whitespace, " "),
+                (TokenKind::Comment, "// a"),
+            ],
+        );
+    }
+
+    #[test]
+    fn tokenizes_losslessly() {
+        check_lossless("fn main() {\r\n    // a\r\n    x.0.1 + 1. + 1..2;\r\n}\r\n");
+        check_lossless("\u{FEFF}#!/usr/bin/env cargo\r\n\n---cargo \r\n[a]\r\n---\r\nfn");
+        check_lossless("/* unterminated \"string € \u{2028} `");
+        check_lossless("<<<<<<< a\r\n\"b\n=======\r\nc\n>>>>>>> d");
+        check_lossless("let größe = r#\"raw\"#; 'a'; b'\\x80'; 0b102");
+    }
+
+    fn check_retokenize(before: &str, range: std::ops::Range<usize>, replacement: &str) {
+        let after = format!(
+            "{}{}{}",
+            &before[..range.start],
+            replacement,
+            &before[range.end..]
+        );
+
+        assert_eq!(
+            retokenize(&tokenize(before), range, replacement, &after),
+            tokenize(&after),
+            "{:?} -> {:?}",
+            before,
+            after,
+        );
+    }
+
+    #[test]
+    fn retokenizes_edit_within_token() {
+        check_retokenize("fn main() {\n    let x = 1;\n}\n", 24..25, "23");
+    }
+
+    #[test]
+    fn retokenizes_edit_opening_and_closing_string() {
+        let input = "a\nlet b = c;\nlet d = \"e\";\nf";
+
+        check_retokenize(input, 10..10, "\"");
+        check_retokenize(input, 21..22, "");
+        check_retokenize(input, 21..21, "\\");
+    }
+
+    #[test]
+    fn retokenizes_edit_opening_and_closing_block_comment() {
+        let input = "a\nb /* c\nd */ e\nf /* g */\nh";
+
+        check_retokenize(input, 2..2, "/*");
+        check_retokenize(input, 12..14, "");
+        check_retokenize(input, 5..5, "*/");
+        check_retokenize(input, 24..26, "/*");
+    }
+
+    #[test]
+    fn retokenizes_edit_to_file_start() {
+        check_retokenize("#!/bin/sh\n---\na\n---\nb\n", 19..22, "");
+        check_retokenize("#!/bin/sh\n---\na\nb\n", 17..17, "---\n");
+        check_retokenize("\u{FEFF}a\nb\nc", 7..8, "d");
+    }
+
+    #[test]
+    fn retokenizes_any_small_edit_like_tokenizing_from_scratch() {
+        let inputs = [
+            "fn f() {\n    let s = \"a\\\"b\"; /* c\n d */ x.0.1 + 1. + 'e' + 'f\n}\n",
+            "<<<<<<< a\r\nb\n=======\nc = \"d\n>>>>>>> e\n// f\r\ng",
+            "r#\"a\n\"# b'\\x' 1e 0x c\n  \t d",
+        ];
+        let replacements = ["", "\"", "'", "/*", "*/", "\n", " ", "<<<<<<<", "a", "."];
+
+        for input in inputs {
+            for start in (0..=input.len()).filter(|idx| input.is_char_boundary(*idx)) {
+                for end in
+                    (start..=input.len().min(start + 3)).filter(|idx| input.is_char_boundary(*idx))
+                {
+                    for replacement in replacements {
+                        check_retokenize(input, start..end, replacement);
+                    }
+                }
+            }
+        }
+    }
+}